@@ -0,0 +1,43 @@
+// Parsing, printing and running lines with very long chains of operations, which should all take
+// time proportional to the length of the chain.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use code::{interpreter::interpreter::InterpreterState, parser::program::program};
+
+const TERMS: [usize; 4] = [1_000, 2_500, 5_000, 10_000];
+
+/// A program that builds a string out of `terms` terms on one line, like the primes example.
+fn source(terms: usize) -> String {
+    format!(
+        "str = the string \nn = 5\nstr = str + n{}\nprint str\n",
+        " + the string , + n".repeat((terms - 2) / 2)
+    )
+}
+
+fn long_expressions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("long_expressions");
+    for terms in TERMS {
+        let source = source(terms);
+        let parsed = program(&source).unwrap();
+        group.throughput(Throughput::Elements(terms as u64));
+
+        group.bench_with_input(BenchmarkId::new("parse", terms), &source, |b, source| {
+            b.iter(|| program(source).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("print", terms), &parsed, |b, parsed| {
+            b.iter(|| parsed.to_string())
+        });
+        group.bench_with_input(BenchmarkId::new("run", terms), &parsed, |b, parsed| {
+            b.iter(|| {
+                let mut interpreter = InterpreterState::capturing();
+                interpreter.run_ref(parsed).unwrap();
+                interpreter.into_output()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, long_expressions);
+criterion_main!(benches);
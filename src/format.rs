@@ -0,0 +1,105 @@
+// Formatting of CODE source.
+//
+// Almost all whitespace in CODE is significant: identifiers and strings can contain (trailing)
+// spaces, and extra spaces after ` = ` or an operator become part of the right hand side. So the
+// formatter only changes things that can't affect the meaning of the program:
+// - each line is printed in its canonical form (e.g. `007` becomes `7`)
+// - indentation is normalised to one space per level of nesting
+// Every line of the input produces exactly one line of output, so `come from` targets stay valid.
+//
+// In particular, spacing around `=` and operators and trailing whitespace are left alone, even
+// though they are the usual things for a formatter to normalise, because changing them would
+// change identifiers and strings.
+
+use crate::parser::{
+    error::ParseError,
+    program::{program, Program},
+};
+
+/// Formats the given source code, or returns the parse errors if it isn't a valid program.
+pub fn format_source(source: &str) -> Result<String, Vec<ParseError>> {
+    let mut program = program(source)?;
+    normalize_indentation(&mut program);
+    Ok(program.to_string())
+}
+
+/// Replaces the indentation of each line by its level of nesting: the number of blocks that it is
+/// inside. Conditionals only compare the indentation of a line with the lines around it and with
+/// the line that ends its block, so this doesn't change the meaning of the program.
+///
+/// If a line is dedented to a depth between those of two enclosing blocks (e.g. lines indented by
+/// 0, 4 and then 2), nesting levels would make it line up with a block that it didn't line up with
+/// before. Then the depths are ranked across the whole program instead, which also keeps the
+/// meaning.
+pub fn normalize_indentation(program: &mut Program) {
+    let levels = nesting_levels(program).unwrap_or_else(|| ranked_depths(program));
+    for (expr, level) in program.expressions.iter_mut().zip(levels) {
+        expr.indent_depth = level;
+    }
+}
+
+/// The nesting level of each line, or `None` if a line is dedented to a depth that no enclosing
+/// block starts at.
+fn nesting_levels(program: &Program) -> Option<Vec<u32>> {
+    // The indent depth of each block that the current line is inside, outermost first
+    let mut blocks: Vec<u32> = Vec::new();
+    let mut levels = Vec::new();
+
+    for expr in &program.expressions {
+        let depth = expr.indent_depth;
+        let mut dedented = false;
+        while blocks.last().is_some_and(|&d| d > depth) {
+            blocks.pop();
+            dedented = true;
+        }
+
+        match blocks.last() {
+            Some(&d) if d == depth => {}
+            _ if dedented => return None,
+            _ => blocks.push(depth),
+        }
+        levels.push(blocks.len() as u32 - 1);
+    }
+
+    Some(levels)
+}
+
+/// The rank of each line's indent depth among all of the depths in the program.
+fn ranked_depths(program: &Program) -> Vec<u32> {
+    let mut depths = program
+        .expressions
+        .iter()
+        .map(|e| e.indent_depth)
+        .collect::<Vec<_>>();
+    depths.sort();
+    depths.dedup();
+
+    program
+        .expressions
+        .iter()
+        .map(|e| depths.binary_search(&e.indent_depth).unwrap() as u32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::format_source;
+
+    #[test]
+    fn indentation_is_nesting_level() {
+        let source = "x = 1\n print x\nx = 2\n  print x\n";
+        assert_eq!(
+            format_source(source).unwrap(),
+            "x = 1\n print x\nx = 2\n print x\n"
+        );
+    }
+
+    #[test]
+    fn inconsistent_dedent_keeps_depth_order() {
+        let source = "x = 1\n    x = 2\n        print x\n  print x\n";
+        assert_eq!(
+            format_source(source).unwrap(),
+            "x = 1\n  x = 2\n   print x\n print x\n"
+        );
+    }
+}
@@ -1,3 +1,6 @@
+pub mod error;
 pub mod interpreter;
 pub mod r#type;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod value;
@@ -0,0 +1,125 @@
+use std::{fmt::Display, io, time::Duration};
+
+use crate::{
+    interpreter::{r#type::Type, value::Value},
+    parser::{error::ParseError, line::identifier::Identifier},
+};
+
+/// The result type of everything that evaluates part of a program.
+pub type RunResult<T> = Result<T, RuntimeError>;
+
+/// An error that stopped a program while it was running.
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub(crate) line_number: usize,
+    pub(crate) expression: String,
+    pub(crate) kind: Box<RuntimeErrorKind>,
+}
+
+/// What went wrong when a program stopped with a `RuntimeError`.
+#[derive(Debug)]
+pub enum RuntimeErrorKind {
+    /// The program contains a line that failed to parse, so it can't be run at all.
+    Parse(ParseError),
+    /// A variable was assigned to without being declared first, while declarations are required.
+    UndeclaredVariable(Identifier),
+    /// Printing a value or reading a line of input failed.
+    Io(io::Error),
+    /// Dividing by zero (or, with `/`, by a multiple of 127). Only an error in strict mode.
+    DivisionByZero { lhs: Value, rhs: Value },
+    /// Converting a string that isn't a number to an int or a z. Only an error in strict mode.
+    InvalidConversion { value: Value, to: Type },
+    /// Assigning through brackets, e.g. `(x) = 5`, where some variable along the way doesn't
+    /// exist. The identifier is the one inside the brackets. Only an error in strict mode.
+    InvalidIndirection(Identifier),
+    /// Calling a function that has no lines. Only an error in strict mode.
+    EmptyFunction(Identifier),
+    /// The run executed as many lines as its fuel allowed, and was about to execute another.
+    OutOfFuel(usize),
+    /// The run went on for longer than its time limit.
+    TimedOut(Duration),
+    /// Functions called each other more deeply than the run allows.
+    CallTooDeep(usize),
+}
+
+impl RuntimeError {
+    pub(crate) fn new(
+        line_number: usize,
+        expression: impl ToString,
+        kind: RuntimeErrorKind,
+    ) -> Self {
+        Self {
+            line_number,
+            expression: expression.to_string(),
+            kind: Box::new(kind),
+        }
+    }
+
+    /// The 1-based line number of the line that was running.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// The expression that was being evaluated, as source code.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn kind(&self) -> &RuntimeErrorKind {
+        &self.kind
+    }
+}
+
+impl From<ParseError> for RuntimeError {
+    fn from(error: ParseError) -> Self {
+        Self {
+            line_number: error.line_number,
+            expression: error.context.trim_start_matches(' ').to_owned(),
+            kind: Box::new(RuntimeErrorKind::Parse(error)),
+        }
+    }
+}
+
+impl Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "cannot run a program with a {}", error.message),
+            Self::UndeclaredVariable(ident) => write!(f, "undeclared variable {ident}"),
+            Self::Io(error) => write!(f, "I/O error: {error}"),
+            Self::DivisionByZero { lhs, rhs } => write!(f, "division by zero: {lhs} by {rhs}"),
+            Self::InvalidConversion { value, to } => {
+                write!(f, "cannot convert {value} to {to}")
+            }
+            Self::InvalidIndirection(ident) => {
+                write!(f, "{ident} doesn't lead to a variable through its brackets")
+            }
+            Self::EmptyFunction(ident) => write!(f, "called function {ident}, which has no lines"),
+            Self::OutOfFuel(fuel) => write!(f, "ran out of fuel after {fuel} lines"),
+            Self::TimedOut(limit) => write!(f, "timed out after {limit:?}"),
+            Self::CallTooDeep(depth) => {
+                write!(f, "functions called each other more than {depth} deep")
+            }
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let line_number = self.line_number.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        writeln!(f, "error: {}", self.kind)?;
+        writeln!(f, "{gutter}--> line {}", self.line_number)?;
+        writeln!(f, "{gutter} |")?;
+        write!(f, "{line_number} | {}", self.expression)
+    }
+}
+
+impl std::error::Error for RuntimeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind.as_ref() {
+            RuntimeErrorKind::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -1,15 +1,24 @@
-use std::collections::HashMap;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Stdin, Stdout, Write},
+    time::{Duration, Instant},
+};
 
 use crate::{
     interpreter::{
+        error::{RunResult, RuntimeError, RuntimeErrorKind},
         r#type::Type,
-        value::{function::Function, int::Int, Value},
+        value::{function::Function, int::Int, record::Record, Value},
     },
     parser::{
         line::{
-            binary_op::Operation, bracketed_identifier::BracketedIdentifier,
-            expression::Expression, identifier::Identifier,
+            bracketed_identifier::BracketedIdentifier,
+            comparison::{Comparator, Comparison},
+            expression::Expression,
+            identifier::Identifier,
             indented_expression::IndentedExpression,
+            logical::Connective,
         },
         program::Program,
     },
@@ -27,32 +36,189 @@ impl Variable {
     }
 }
 
-#[derive(Default)]
-pub struct InterpreterState {
+/// The state of a running program. Whatever the program prints is written to `output`, and
+/// `input` reads lines from `input`. These are stdout and stdin by default.
+pub struct InterpreterState<O: Write = Stdout, I: BufRead = BufReader<Stdin>> {
+    output: O,
+    input: I,
     variables: HashMap<Identifier, Variable>,
+    /// The name and type of each field of each record type, by the name of the record type.
+    record_types: HashMap<String, Vec<(Identifier, Type)>>,
+    /// Whether variables have to be declared with a type before they are assigned to.
+    require_declarations: bool,
+    /// Whether operations that would give a sentinel result (127 for an int, or `i128::MAX` for a
+    /// z) are errors instead.
+    strict: bool,
+    /// The most lines that a run can execute before it is stopped.
+    fuel: Option<usize>,
+    /// How long a run can take before it is stopped.
+    time_limit: Option<Duration>,
+    /// When the current run will be stopped, from its time limit.
+    deadline: Option<Instant>,
+    /// The number of lines executed so far in the current run.
+    steps: usize,
+    /// How deeply functions can call each other before the run is stopped.
+    max_call_depth: Option<usize>,
+    /// The number of function calls that are currently running.
+    call_depth: usize,
+}
+
+/// What a program did when it ran to the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunOutcome {
+    /// The value of the last line that was executed, if any (not counting conditionals).
+    pub value: Option<Value>,
+    /// The number of lines that were executed, including conditionals and the lines of functions.
+    pub steps: usize,
 }
 
 impl InterpreterState {
     pub fn new() -> Self {
-        Self::default()
+        Self::with_output(io::stdout())
+    }
+}
+
+impl Default for InterpreterState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InterpreterState<Vec<u8>> {
+    /// An interpreter that keeps everything the program prints, rather than writing it to stdout.
+    pub fn capturing() -> Self {
+        Self::with_output(Vec::new())
+    }
+}
+
+impl<I: BufRead> InterpreterState<Vec<u8>, I> {
+    /// Everything the program has printed so far, one value per line.
+    pub fn printed(&self) -> String {
+        String::from_utf8_lossy(&self.output).into_owned()
+    }
+}
+
+impl<O: Write> InterpreterState<O> {
+    /// An interpreter that writes everything the program prints to `output`.
+    pub fn with_output(output: O) -> Self {
+        Self {
+            output,
+            input: BufReader::new(io::stdin()),
+            variables: HashMap::new(),
+            record_types: HashMap::new(),
+            require_declarations: false,
+            strict: false,
+            fuel: None,
+            time_limit: None,
+            deadline: None,
+            steps: 0,
+            max_call_depth: None,
+            call_depth: 0,
+        }
+    }
+}
+
+impl<O: Write, I: BufRead> InterpreterState<O, I> {
+    /// Makes `input` read lines from `input` instead of stdin.
+    pub fn with_input<J: BufRead>(self, input: J) -> InterpreterState<O, J> {
+        InterpreterState {
+            output: self.output,
+            input,
+            variables: self.variables,
+            record_types: self.record_types,
+            require_declarations: self.require_declarations,
+            strict: self.strict,
+            fuel: self.fuel,
+            time_limit: self.time_limit,
+            deadline: self.deadline,
+            steps: self.steps,
+            max_call_depth: self.max_call_depth,
+            call_depth: self.call_depth,
+        }
+    }
+
+    /// The sink that the program prints to.
+    pub fn output(&self) -> &O {
+        &self.output
+    }
+
+    /// Stops the interpreter, giving back the sink that the program printed to.
+    pub fn into_output(self) -> O {
+        self.output
+    }
+
+    /// Makes assigning to a variable that hasn't been declared (with e.g. `x = int`) an error,
+    /// rather than creating the variable with the type of the value.
+    pub fn require_declarations(mut self, require: bool) -> Self {
+        self.require_declarations = require;
+        self
+    }
+
+    /// Makes the operations that normally give 127 (or `i128::MAX` for a z) when they fail into
+    /// errors: dividing by zero, converting a string that isn't a number to an int or a z,
+    /// assigning through brackets that don't lead to a variable, and calling a function with no
+    /// lines.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Stops each run with an error once it has executed `fuel` lines, counting conditionals and
+    /// the lines of functions.
+    pub fn fuel(mut self, fuel: usize) -> Self {
+        self.fuel = Some(fuel);
+        self
+    }
+
+    /// Stops each run with an error once it has been running for `time_limit`. The time is only
+    /// checked between lines.
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Stops each run with an error when functions call each other more than `depth` deep, e.g. a
+    /// function that calls itself forever. Each call takes up some of the native stack, so without
+    /// this, such a run overflows the stack of the thread it's running on.
+    pub fn max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = Some(depth);
+        self
+    }
+
+    /// Runs the program, keeping any variables from previous runs.
+    pub fn run(&mut self, program: Program) -> RunResult<RunOutcome> {
+        self.run_ref(&program)
     }
 
-    pub fn run(&mut self, program: Program) {
+    /// Runs the program without taking ownership of it, keeping any variables from previous runs.
+    pub fn run_ref(&mut self, program: &Program) -> RunResult<RunOutcome> {
+        // Programs containing lines that failed to parse can be inspected, but not run
+        if let Some(error) = program.errors().next() {
+            return Err(error.clone().into());
+        }
+
         let lines = program
             .expressions
-            .into_iter()
+            .iter()
             .enumerate()
             .map(|(i, l)| (i + 1, l))
-            .collect::<Vec<_>>();
+            .collect();
 
-        self.eval_lines(&lines);
+        self.steps = 0;
+        self.call_depth = 0;
+        self.deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        let value = self.eval_lines(lines)?;
+        Ok(RunOutcome {
+            value,
+            steps: self.steps,
+        })
     }
 
-    fn eval_lines(&mut self, lines: &[(usize, IndentedExpression)]) -> Option<Value> {
+    fn eval_lines(&mut self, lines: Vec<(usize, &IndentedExpression)>) -> RunResult<Option<Value>> {
         let come_froms = lines
             .iter()
             .filter_map(|(idx, e)| match &e.expr {
-                Expression::ComeFrom(c) => Some((c.line_number.0 as usize, idx + 1)),
+                Expression::ComeFrom(c) => Some((c.line_number.value as usize, idx + 1)),
                 _ => None,
             })
             .collect::<HashMap<_, _>>();
@@ -61,14 +227,28 @@ impl InterpreterState {
 
         // Sort lines
         let lines = {
-            let mut v = lines.iter().collect::<Vec<_>>();
-            v.sort_by_key(|(l, _)| l);
+            let mut v = lines;
+            v.sort_by_key(|(l, _)| *l);
             v
         };
 
         let mut current_idx = 0;
 
         while let Some((line_number, expr)) = lines.get(current_idx) {
+            if let Some(fuel) = self.fuel
+                && self.steps >= fuel
+            {
+                let kind = RuntimeErrorKind::OutOfFuel(fuel);
+                return Err(RuntimeError::new(*line_number, &expr.expr, kind));
+            }
+            if let Some(deadline) = self.deadline
+                && Instant::now() >= deadline
+            {
+                let kind = RuntimeErrorKind::TimedOut(self.time_limit.unwrap_or_default());
+                return Err(RuntimeError::new(*line_number, &expr.expr, kind));
+            }
+            self.steps += 1;
+
             // If the next line in `lines` is the actual next line (i.e. the line number is
             // `line_number + 1`), and the line is indented, then this line should be interpreted
             // as a conditional.
@@ -76,7 +256,7 @@ impl InterpreterState {
                 && *next_line_number == line_number + 1
                 && next_expr.indent_depth > expr.indent_depth
             {
-                if self.eval_conditional((*line_number, &expr.expr)) == Int(1) {
+                if self.eval_conditional((*line_number, &expr.expr))? == Int(1) {
                     // Conditional evaluated to true, so go to the next line
                     current_idx += 1;
                 } else {
@@ -90,7 +270,19 @@ impl InterpreterState {
                         .map(|l| l + current_idx + 1)
                     {
                         Some(new_idx) => {
-                            current_idx = new_idx;
+                            // If the block is followed by an `else` for this guard, go into the
+                            // `else` block instead
+                            let (_, next) = lines[new_idx];
+                            let is_else =
+                                |e: &IndentedExpression| matches!(e.expr, Expression::Else(_));
+                            current_idx = if is_else(next)
+                                && !is_else(expr)
+                                && next.indent_depth == expr.indent_depth
+                            {
+                                new_idx + 1
+                            } else {
+                                new_idx
+                            };
                             continue;
                         }
                         None => break,
@@ -98,7 +290,7 @@ impl InterpreterState {
                 }
             } else {
                 // Not a conditional, so just evaluate the expression normally.
-                value = Some(self.eval_expression((*line_number, &expr.expr)));
+                value = Some(self.eval_expression((*line_number, &expr.expr))?);
 
                 // Check for `come from` jumps
                 if let Some(&to) = come_froms.get(line_number) {
@@ -115,18 +307,43 @@ impl InterpreterState {
             }
         }
 
-        value
+        Ok(value)
     }
 
-    fn eval_expression(&mut self, (line_number, expr): (usize, &Expression)) -> Value {
+    fn eval_expression(&mut self, (line_number, expr): (usize, &Expression)) -> RunResult<Value> {
         match expr {
             Expression::Equality(eq) => {
                 let Some(ident) = self.resolve_bracketed_identifier(&eq.lhs) else {
+                    if self.strict {
+                        let kind = RuntimeErrorKind::InvalidIndirection(eq.lhs.identifier.clone());
+                        return Err(RuntimeError::new(line_number, expr, kind));
+                    }
+
                     // The LHS of the equality is invalid. Set the inner identifier to 127.
                     self.set_variable_or_create(eq.lhs.identifier.clone(), Value::Int(Int(127)));
-                    return Value::Int(Int(127));
+                    return Ok(Value::Int(Int(127)));
                 };
 
+                // `point = record x int, y int` defines a record type
+                if let Expression::RecordType(record_type) = &eq.rhs.expr
+                    && eq.lhs.fields.is_empty()
+                {
+                    let fields = record_type
+                        .fields
+                        .iter()
+                        .map(|(name, type_name)| {
+                            (name.clone(), Type::from(type_name.name.as_ref()))
+                        })
+                        .collect();
+                    self.record_types.insert(ident.name, fields);
+                    return Ok(Value::Int(Int(0)));
+                }
+
+                if !eq.lhs.fields.is_empty() {
+                    let rhs = self.eval_expression((line_number, &eq.rhs.expr))?;
+                    return self.set_field((line_number, expr), &ident, &eq.lhs.fields, rhs);
+                }
+
                 // If ident is a function variable, just append the line and return immediately
                 if let Some(v) = self.variables.get_mut(&ident) {
                     if v.value == Value::Uninitialized(Type::Function) {
@@ -136,71 +353,115 @@ impl InterpreterState {
                     if let Value::Function(f) = &mut v.value {
                         let a = (line_number, *eq.rhs.clone());
                         f.lines.push(a);
-                        return Value::Int(Int(0));
+                        return Ok(Value::Int(Int(0)));
                     }
                 }
 
                 // Eval the RHS
-                let mut rhs = self.eval_expression((line_number, &eq.rhs.expr));
+                let mut rhs = self.eval_expression((line_number, &eq.rhs.expr))?;
 
                 if let Some(var) = self.variables.get_mut(&ident) {
                     // We already dealt with the case of `var` being a function, so we can just set
                     // the value here
+                    if self.strict
+                        && let Some(kind) = rhs.conversion_fault(&var.value.r#type())
+                    {
+                        return Err(RuntimeError::new(line_number, expr, kind));
+                    }
                     rhs.cast(var.value.r#type());
                     var.set_value(rhs.clone());
 
-                    rhs
+                    Ok(rhs)
                 } else {
-                    // Create a variable with RHS as the type name
-                    let var_type = match &eq.rhs.expr {
-                        Expression::Identifier(ident) => Type::from(ident.0.as_ref()),
-                        _ => todo!(),
+                    // Create a variable. If the RHS is the name of a type, that is the type of the
+                    // variable, and otherwise the type is that of the value of the RHS.
+                    let declared_type = match &eq.rhs.expr {
+                        Expression::Identifier(name) => self.type_named(&name.name),
+                        _ => None,
+                    };
+                    let value = match declared_type {
+                        Some(var_type) => self.uninitialized(var_type),
+                        None if self.require_declarations => {
+                            return Err(RuntimeError::new(
+                                line_number,
+                                expr,
+                                RuntimeErrorKind::UndeclaredVariable(ident),
+                            ));
+                        }
+                        // The RHS has already been evaluated, and evaluating it again would
+                        // repeat any input or output that it does
+                        None => rhs,
                     };
-                    let value = Value::Uninitialized(var_type);
                     self.create_variable(ident, value.clone());
-                    value
+                    Ok(value)
                 }
             }
-            Expression::ComeFrom(_) => Value::Int(Int(0)),
+            Expression::ComeFrom(_) => Ok(Value::Int(Int(0))),
             Expression::Print(p) => {
-                let value = self.eval_expression((line_number, &p.0));
-                println!("{value}");
-                value
+                let value = self.eval_expression((line_number, &p.expr))?;
+                // Flush straight away, so that the output is there before any input is read
+                writeln!(self.output, "{value}")
+                    .and_then(|()| self.output.flush())
+                    .map_err(|e| RuntimeError::new(line_number, expr, RuntimeErrorKind::Io(e)))?;
+                Ok(value)
             }
             Expression::BinaryOp(op) => {
-                let lhs = self.eval_expression((line_number, &op.lhs));
-                let rhs = self.eval_expression((line_number, &op.rhs));
-                match op.op {
-                    Operation::Add => lhs + rhs,
-                    Operation::Sub => lhs - rhs,
-                    Operation::Mul => lhs * rhs,
-                    Operation::Div => lhs / rhs,
-                    Operation::ModularDiv => lhs.modular_div(rhs),
-                    Operation::Mod => lhs % rhs,
+                // Evaluate the chain from left to right in a loop, since it can be too long to
+                // recurse along
+                let (first, ops) = op.chain();
+                let mut lhs = self.eval_expression((line_number, first))?;
+                for op in ops {
+                    let rhs = self.eval_expression((line_number, &op.rhs))?;
+                    lhs = if self.strict {
+                        lhs.checked_operation(op.op, rhs)
+                            .map_err(|kind| RuntimeError::new(line_number, op, *kind))?
+                    } else {
+                        lhs.operation(op.op, rhs)
+                    };
                 }
+                Ok(lhs)
             }
-            Expression::Identifier(ident) => {
-                if let Some(var) = self.variables.get(ident) {
-                    if let Value::Function(f) = &var.value {
-                        // Variable is a function, so call the function
-                        self.call_function(&f.clone())
-                    } else {
-                        // Not a function, so return the value of the variable
-                        var.value.clone()
+            Expression::Comparison(c) => Ok(Value::Int(self.eval_comparison(line_number, c)?)),
+            // Connectives are always evaluated as conditionals
+            Expression::Logical(_) | Expression::Not(_) => {
+                Ok(Value::Int(self.eval_conditional((line_number, expr))?))
+            }
+            Expression::Group(g) => self.eval_expression((line_number, &g.expr)),
+            Expression::Identifier(ident) => self.eval_identifier(line_number, ident),
+            Expression::BracketedIdentifier(bracketed) => {
+                let Some(ident) = self.resolve_bracketed_identifier(bracketed) else {
+                    return Ok(Value::Uninitialized(Type::Int));
+                };
+
+                let mut value = self.eval_identifier(line_number, &ident)?;
+                for field in &bracketed.fields {
+                    value = match value {
+                        Value::Record(r) => r.field(field).cloned(),
+                        _ => None,
                     }
-                } else {
-                    Value::Uninitialized(Type::Int)
+                    .unwrap_or(Value::Uninitialized(Type::Int));
                 }
+                Ok(value)
             }
-            Expression::Literal(lit) => lit.into(),
-            Expression::None => Value::Uninitialized(Type::Int),
+            Expression::Literal(lit) => Ok(lit.into()),
+            Expression::ReadInput(_) => self
+                .read_line()
+                .map_err(|e| RuntimeError::new(line_number, expr, RuntimeErrorKind::Io(e))),
+            Expression::RecordType(_)
+            | Expression::Else(_)
+            | Expression::None
+            | Expression::Continuation => Ok(Value::Uninitialized(Type::Int)),
+            Expression::Error(_) => unreachable!("Program contains a line that failed to parse"),
         }
     }
 
-    pub fn eval_conditional(&mut self, (line_number, expr): (usize, &Expression)) -> Int {
+    pub fn eval_conditional(
+        &mut self,
+        (line_number, expr): (usize, &Expression),
+    ) -> RunResult<Int> {
         match expr {
             Expression::Equality(eq) => {
-                let rhs = self.eval_expression((line_number, &eq.rhs.expr));
+                let rhs = self.eval_expression((line_number, &eq.rhs.expr))?;
 
                 // Value of the LHS variable, or an uninitialized int if it's not defined
                 let mut lhs = self
@@ -209,20 +470,229 @@ impl InterpreterState {
                     .map(|v| &v.value)
                     .unwrap_or_else(|| &Value::Uninitialized(Type::Int))
                     .to_owned();
+                if self.strict
+                    && let Some(kind) = lhs.conversion_fault(&rhs.r#type())
+                {
+                    return Err(RuntimeError::new(line_number, expr, kind));
+                }
                 lhs.cast(rhs.r#type());
 
-                Int((lhs == rhs) as u8)
+                Ok(Int((lhs == rhs) as u8))
+            }
+            Expression::Comparison(c) => self.eval_comparison(line_number, c),
+            Expression::Logical(l) => {
+                // Go along the chain in a loop, since it can be too long to recurse along
+                let (first, links) = l.chain();
+                let mut value = self.eval_conditional((line_number, first))?;
+                for link in links {
+                    value = match (link.connective, value) {
+                        (Connective::And, Int(0)) => Int(0),
+                        (Connective::Or, Int(1)) => Int(1),
+                        _ => self.eval_conditional((line_number, &link.rhs))?,
+                    };
+                }
+                Ok(value)
+            }
+            Expression::Not(n) => {
+                Ok(Int((self.eval_conditional((line_number, &n.expr))? == Int(0)) as u8))
+            }
+            Expression::Group(g) => self.eval_conditional((line_number, &g.expr)),
+            // A variable that holds a function is true if the function has any lines, and it isn't
+            // called
+            Expression::Identifier(ident) => Ok(Int(self.variable_is_truthy(ident, &[]) as u8)),
+            Expression::BracketedIdentifier(bracketed) => {
+                let truthy = self
+                    .resolve_bracketed_identifier(bracketed)
+                    .is_some_and(|ident| self.variable_is_truthy(&ident, &bracketed.fields));
+                Ok(Int(truthy as u8))
+            }
+            Expression::BinaryOp(_) | Expression::Literal(_) | Expression::ReadInput(_) => {
+                let value = self.eval_expression((line_number, expr))?;
+                Ok(Int(value.is_truthy() as u8))
+            }
+            Expression::ComeFrom(_)
+            | Expression::Print(_)
+            | Expression::RecordType(_)
+            // Reaching an `else` other than from its guard skips its block
+            | Expression::Else(_)
+            | Expression::None
+            | Expression::Continuation => Ok(Int(0)),
+            Expression::Error(_) => unreachable!("Program contains a line that failed to parse"),
+        }
+    }
+
+    /// Reads the next line of the input as a string, without its line ending. At the end of the
+    /// input, the result is an uninitialised string.
+    fn read_line(&mut self) -> io::Result<Value> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(Value::Uninitialized(Type::String));
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Value::String(line))
+    }
+
+    /// The type with the given name, if it is a built-in type or a record type that has been
+    /// defined.
+    fn type_named(&self, name: &str) -> Option<Type> {
+        Type::builtin(name).or_else(|| {
+            self.record_types
+                .contains_key(name)
+                .then(|| Type::Custom(name.to_owned()))
+        })
+    }
+
+    /// A new value of the given type. For a record type, this is a record whose fields are all
+    /// uninitialised.
+    fn uninitialized(&self, var_type: Type) -> Value {
+        match &var_type {
+            Type::Custom(name) => match self.record_types.get(name) {
+                Some(fields) => new_record(name, fields),
+                None => Value::Uninitialized(var_type),
+            },
+            _ => Value::Uninitialized(var_type),
+        }
+    }
+
+    /// Sets a field of a record variable, e.g. `p.x = 5`, casting the value to the type of the
+    /// field. If the variable isn't a record, or doesn't have the field, nothing is set and the
+    /// result is 127.
+    fn set_field(
+        &mut self,
+        (line_number, expr): (usize, &Expression),
+        ident: &Identifier,
+        fields: &[Identifier],
+        mut value: Value,
+    ) -> RunResult<Value> {
+        let Some(var) = self.variables.get_mut(ident) else {
+            return Ok(Value::Int(Int(127)));
+        };
+
+        let mut target = &mut var.value;
+        for field in fields {
+            // A record inside a record is only created when one of its fields is first set
+            let record_type = match target {
+                Value::Uninitialized(Type::Custom(name)) => {
+                    self.record_types.get_key_value(name.as_str())
+                }
+                _ => None,
+            };
+            if let Some((name, record_fields)) = record_type {
+                *target = new_record(name, record_fields);
+            }
+
+            let Some(field) = (match target {
+                Value::Record(r) => r.field_mut(field),
+                _ => None,
+            }) else {
+                return Ok(Value::Int(Int(127)));
+            };
+            target = field;
+        }
+
+        if self.strict
+            && let Some(kind) = value.conversion_fault(&target.r#type())
+        {
+            return Err(RuntimeError::new(line_number, expr, kind));
+        }
+        value.cast(target.r#type());
+        *target = value.clone();
+        Ok(value)
+    }
+
+    fn eval_identifier(&mut self, line_number: usize, ident: &Identifier) -> RunResult<Value> {
+        if let Some(var) = self.variables.get(ident) {
+            // A function that has been declared but not given any lines is still uninitialised
+            if self.strict && var.value == Value::Uninitialized(Type::Function) {
+                let kind = RuntimeErrorKind::EmptyFunction(ident.clone());
+                return Err(RuntimeError::new(line_number, ident, kind));
+            }
+
+            if let Value::Function(f) = &var.value {
+                if self.strict && f.lines.is_empty() {
+                    let kind = RuntimeErrorKind::EmptyFunction(ident.clone());
+                    return Err(RuntimeError::new(line_number, ident, kind));
+                }
+
+                if let Some(max_call_depth) = self.max_call_depth
+                    && self.call_depth >= max_call_depth
+                {
+                    let kind = RuntimeErrorKind::CallTooDeep(max_call_depth);
+                    return Err(RuntimeError::new(line_number, ident, kind));
+                }
+
+                // Variable is a function, so call the function
+                let f = f.clone();
+                self.call_depth += 1;
+                let value = self.call_function(&f);
+                self.call_depth -= 1;
+                value
+            } else {
+                // Not a function, so return the value of the variable
+                Ok(var.value.clone())
+            }
+        } else {
+            Ok(Value::Uninitialized(Type::Int))
+        }
+    }
+
+    /// Whether a variable, or a field of it, is true when it's used as a conditional. Unlike
+    /// evaluating it, this doesn't call it if it's a function.
+    fn variable_is_truthy(&self, ident: &Identifier, fields: &[Identifier]) -> bool {
+        let mut value = self.variables.get(ident).map(|v| &v.value);
+        for field in fields {
+            value = match value {
+                Some(Value::Record(r)) => r.field(field),
+                _ => None,
+            };
+        }
+        value.is_some_and(Value::is_truthy)
+    }
+
+    /// Compares two values in the same way as the equality in a conditional: the LHS is cast to
+    /// the type of the RHS, and then they are compared. In a chain of comparisons, the result of
+    /// each one is the LHS of the next.
+    fn eval_comparison(&mut self, line_number: usize, comparison: &Comparison) -> RunResult<Int> {
+        // Go along the chain in a loop, since it can be too long to recurse along
+        let (first, links) = comparison.chain();
+        let mut lhs = self.eval_expression((line_number, first))?;
+        let mut result = Int(0);
+        for link in links {
+            let rhs = self.eval_expression((line_number, &link.rhs))?;
+            if self.strict
+                && let Some(kind) = lhs.conversion_fault(&rhs.r#type())
+            {
+                return Err(RuntimeError::new(line_number, link, kind));
             }
-            Expression::BinaryOp(_) => todo!(),
-            Expression::Literal(_) => todo!(),
-            Expression::Identifier(_) => todo!(),
-            Expression::ComeFrom(_) | Expression::Print(_) | Expression::None => Int(0),
+            lhs.cast(rhs.r#type());
+
+            let ordering = lhs.compare(&rhs);
+            let holds = match link.comparator {
+                Comparator::Less => ordering == Some(Ordering::Less),
+                Comparator::Greater => ordering == Some(Ordering::Greater),
+                Comparator::LessEqual => {
+                    matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+                }
+                Comparator::GreaterEqual => {
+                    matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                }
+                Comparator::NotEqual => lhs != rhs,
+            };
+            result = Int(holds as u8);
+            lhs = Value::Int(result);
         }
+        Ok(result)
     }
 
-    pub fn call_function(&mut self, function: &Function) -> Value {
-        self.eval_lines(&function.lines)
-            .unwrap_or(Value::Int(Int(127)))
+    pub fn call_function(&mut self, function: &Function) -> RunResult<Value> {
+        let lines = function.lines.iter().map(|(l, e)| (*l, e)).collect();
+        Ok(self.eval_lines(lines)?.unwrap_or(Value::Int(Int(127))))
     }
 
     pub fn create_variable(&mut self, name: Identifier, value: Value) {
@@ -247,7 +717,7 @@ impl InterpreterState {
         let mut ident = bracketed.identifier.clone();
         for _ in 0..bracketed.num_brackets {
             if let Some(var) = self.variables.get(&ident) {
-                ident = Identifier(var.value.to_string());
+                ident = Identifier::from(var.value.to_string());
             } else {
                 return None;
             }
@@ -256,3 +726,150 @@ impl InterpreterState {
         Some(ident)
     }
 }
+
+fn new_record(type_name: &str, fields: &[(Identifier, Type)]) -> Value {
+    Value::Record(Record {
+        type_name: type_name.to_owned(),
+        fields: fields
+            .iter()
+            .map(|(name, field_type)| (name.clone(), Value::Uninitialized(field_type.clone())))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        interpreter::{
+            error::RuntimeErrorKind,
+            interpreter::InterpreterState,
+            r#type::Type,
+            testing::{run, run_err},
+        },
+        parser::program::program,
+    };
+
+    /// A program that never stops.
+    const FOREVER: &str = "x = 0\ncome from 3\nx = x + 1\n";
+
+    #[test]
+    fn nested_blocks() {
+        let source = "\
+x = 1
+y = 0
+x
+ print 1
+ y
+  print 2
+ else
+  print 3
+  x
+   print 4
+ print 5
+print 6
+";
+        assert_eq!(run(source), "1\n3\n4\n5\n6\n");
+    }
+
+    #[test]
+    fn false_guard_skips_nested_blocks() {
+        let source = "\
+x = 0
+x
+ print 1
+ x = 0
+  print 2
+ print 3
+else
+ print 4
+print 5
+";
+        assert_eq!(run(source), "4\n5\n");
+    }
+
+    #[test]
+    fn function_guard_is_not_called() {
+        let source = "\
+f = fctn
+g = fctn
+f = print 7
+f
+ print 1
+g
+ print 2
+";
+        assert_eq!(run(source), "1\n");
+    }
+
+    #[test]
+    fn new_variables_have_the_type_of_their_value() {
+        let source =
+            "x = 5\ny = 200\ns = the string 5\nx = x + 125\ny = y + 1000\nprint x\nprint y\n";
+        let mut interpreter = InterpreterState::capturing();
+        interpreter.run(program(source).unwrap()).unwrap();
+
+        for (name, r#type) in [("x", Type::Int), ("y", Type::Z), ("s", Type::String)] {
+            let var = interpreter.variables.values().find(|v| v.name.name == name);
+            assert_eq!(var.unwrap().value.r#type(), r#type, "{name}");
+        }
+        assert_eq!(interpreter.printed(), "3\n1200\n");
+    }
+
+    #[test]
+    fn undeclared_variables_are_errors_when_declarations_are_required() {
+        let source = "x = int\nx = 5\ny = 6\n";
+        let error = run_err(
+            InterpreterState::capturing().require_declarations(true),
+            source,
+        );
+        assert!(matches!(
+            error.kind(),
+            RuntimeErrorKind::UndeclaredVariable(ident) if ident.name() == "y"
+        ));
+        assert_eq!(error.line_number(), 3);
+        assert_eq!(run(source), "");
+    }
+
+    #[test]
+    fn fuel_stops_a_run() {
+        let mut interpreter = InterpreterState::capturing().fuel(100);
+        let error = interpreter.run(program(FOREVER).unwrap()).unwrap_err();
+        assert!(matches!(error.kind(), RuntimeErrorKind::OutOfFuel(100)));
+
+        let outcome = interpreter
+            .run(program("x = 1\nprint x\n").unwrap())
+            .unwrap();
+        assert_eq!(outcome.steps, 2);
+    }
+
+    #[test]
+    fn time_limit_stops_a_run() {
+        let interpreter = InterpreterState::capturing().time_limit(Duration::from_millis(10));
+        let error = run_err(interpreter, FOREVER);
+        assert!(matches!(error.kind(), RuntimeErrorKind::TimedOut(_)));
+    }
+
+    #[test]
+    fn recursion_stops_at_the_call_depth() {
+        let source = "f = fctn\nf = f\nx = f\n";
+        let error = run_err(InterpreterState::capturing().max_call_depth(3), source);
+        assert!(matches!(error.kind(), RuntimeErrorKind::CallTooDeep(3)));
+        assert_eq!(error.line_number(), 2);
+    }
+
+    #[test]
+    fn recursion_has_no_call_depth_by_default() {
+        // Each call takes up a lot of stack in a debug build, so run on a bigger stack than a test
+        // thread's
+        let source = "n = 100\nf = fctn\nf = n = n - 1\nf = n ≠ 0\nf =  y = f\ny = f\nprint n\n";
+        let printed = std::thread::Builder::new()
+            .stack_size(64 << 20)
+            .spawn(|| run(source))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(printed, "0\n");
+    }
+}
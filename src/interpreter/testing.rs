@@ -0,0 +1,30 @@
+// Helpers for tests that run whole programs.
+
+use std::io::BufRead;
+
+use crate::{
+    interpreter::{error::RuntimeError, interpreter::InterpreterState},
+    parser::program::program,
+};
+
+/// Runs the program, and returns everything that it printed.
+pub(crate) fn run(source: &str) -> String {
+    run_with(InterpreterState::capturing(), source)
+}
+
+/// Runs the program with `interpreter`, and returns everything that it printed.
+pub(crate) fn run_with<I: BufRead>(
+    mut interpreter: InterpreterState<Vec<u8>, I>,
+    source: &str,
+) -> String {
+    interpreter.run(program(source).unwrap()).unwrap();
+    interpreter.printed()
+}
+
+/// Runs the program with `interpreter`, and returns the error that stopped it.
+pub(crate) fn run_err<I: BufRead>(
+    mut interpreter: InterpreterState<Vec<u8>, I>,
+    source: &str,
+) -> RuntimeError {
+    interpreter.run(program(source).unwrap()).unwrap_err()
+}
@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
     Int,
@@ -7,6 +9,16 @@ pub enum Type {
     Custom(String),
 }
 
+impl Type {
+    /// The built-in type with the given name, if there is one.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match Self::from(name) {
+            Self::Custom(_) => None,
+            t => Some(t),
+        }
+    }
+}
+
 impl From<&str> for Type {
     fn from(value: &str) -> Self {
         match value {
@@ -18,3 +30,15 @@ impl From<&str> for Type {
         }
     }
 }
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int => f.write_str("int"),
+            Self::Z => f.write_str("z"),
+            Self::String => f.write_str("string"),
+            Self::Function => f.write_str("fctn"),
+            Self::Custom(name) => f.write_str(name),
+        }
+    }
+}
@@ -1,18 +1,24 @@
 pub mod function;
 pub mod int;
+pub mod record;
 pub mod z;
 
 use std::{
+    cmp::Ordering,
     fmt::Display,
     ops::{Add, Div, Mul, Rem, Sub},
 };
 
 use crate::{
     interpreter::{
+        error::RuntimeErrorKind,
         r#type::Type,
-        value::{function::Function, int::Int, z::Z},
+        value::{function::Function, int::Int, record::Record, z::Z},
+    },
+    parser::line::{
+        binary_op::Operation,
+        literal::{IntegerLit, IntegerSuffix, Literal, StringLit},
     },
-    parser::line::literal::{IntegerLit, Literal, StringLit},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -21,6 +27,7 @@ pub enum Value {
     Z(Z),
     String(String),
     Function(Function),
+    Record(Record),
     Uninitialized(Type),
 }
 
@@ -31,6 +38,7 @@ impl Display for Value {
             Value::Z(z) => f.write_str(&z.0.to_string()),
             Value::String(s) => f.write_str(s),
             Value::Function(_) => f.write_str("fctn"),
+            Value::Record(r) => r.fmt(f),
             Value::Uninitialized(_) => f.write_str("nothing"),
         }
     }
@@ -39,15 +47,18 @@ impl Display for Value {
 impl<'a> From<&'a Literal> for Value {
     fn from(value: &'a Literal) -> Self {
         match value {
-            Literal::Integer(IntegerLit(n)) => {
+            Literal::Integer(IntegerLit {
+                value: n, suffix, ..
+            }) => {
                 let n = *n;
-                if let Ok(n) = n.try_into() {
-                    Self::Int(Int(n))
-                } else {
-                    Self::Z(Z(n))
+                match suffix {
+                    // The parser checks that `int` literals are in range
+                    Some(IntegerSuffix::Int) => Self::Int(Int(n as u8)),
+                    None if (0..127).contains(&n) => Self::Int(Int(n as u8)),
+                    Some(IntegerSuffix::Z) | None => Self::Z(Z(n)),
                 }
             }
-            Literal::String(StringLit(s)) => Self::String(s.to_owned()),
+            Literal::String(StringLit { value: s, .. }) => Self::String(s.to_owned()),
         }
     }
 }
@@ -59,6 +70,7 @@ impl Value {
             Value::Z(_) => Type::Z,
             Value::String(_) => Type::String,
             Value::Function(_) => Type::Function,
+            Value::Record(r) => Type::Custom(r.type_name.clone()),
             Value::Uninitialized(t) => t.clone(),
         }
     }
@@ -68,7 +80,7 @@ impl Value {
             Value::Int(n) => *n,
             Value::Z(z) => Int(z.0.try_into().unwrap_or(127)),
             Value::String(s) => s.parse().unwrap_or(Int(127)),
-            Value::Function(_) | Value::Uninitialized(_) => Int(127),
+            Value::Function(_) | Value::Record(_) | Value::Uninitialized(_) => Int(127),
         }
     }
 
@@ -77,7 +89,46 @@ impl Value {
             Value::Int(n) => Z(n.0 as i128),
             Value::Z(z) => *z,
             Value::String(s) => s.parse().unwrap_or(Z(i128::MAX)),
-            Value::Function(_) | Value::Uninitialized(_) => Z(i128::MAX),
+            Value::Function(_) | Value::Record(_) | Value::Uninitialized(_) => Z(i128::MAX),
+        }
+    }
+
+    /// Whether the value counts as true when it's used as a conditional: non-zero numbers,
+    /// non-empty strings and functions, and records with any true field. Uninitialised values are
+    /// false.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Int(n) => n.0 != 0,
+            Value::Z(z) => z.0 != 0,
+            Value::String(s) => !s.is_empty(),
+            Value::Function(f) => !f.lines.is_empty(),
+            Value::Record(r) => r.fields.iter().any(|(_, v)| v.is_truthy()),
+            Value::Uninitialized(_) => false,
+        }
+    }
+
+    /// The order of two values of the same type, which is used by `<`, `>`, `≤` and `≥`. Ints
+    /// (which are the residues 0 to 126) and zs are ordered numerically, strings
+    /// lexicographically, functions by their number of lines, and records of the same type by each
+    /// of their fields in turn. Uninitialised values are only comparable to each other. Values of
+    /// different types aren't comparable.
+    pub fn compare(&self, rhs: &Value) -> Option<Ordering> {
+        match (self, rhs) {
+            (Value::Int(a), Value::Int(b)) => Some(a.0.cmp(&b.0)),
+            (Value::Z(a), Value::Z(b)) => Some(a.0.cmp(&b.0)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (Value::Function(a), Value::Function(b)) => Some(a.lines.len().cmp(&b.lines.len())),
+            (Value::Record(a), Value::Record(b)) if a.type_name == b.type_name => {
+                for ((_, a), (_, b)) in a.fields.iter().zip(&b.fields) {
+                    match a.compare(b)? {
+                        Ordering::Equal => {}
+                        ordering => return Some(ordering),
+                    }
+                }
+                Some(Ordering::Equal)
+            }
+            (Value::Uninitialized(_), Value::Uninitialized(_)) => Some(Ordering::Equal),
+            _ => None,
         }
     }
 
@@ -94,35 +145,86 @@ impl Value {
             Type::Z => *self = Value::Z(self.to_z()),
             Type::String => *self = Value::String(self.to_string()),
             Type::Function => *self = Value::Function(self.to_function()),
-            Type::Custom(_) => todo!(),
+            // Only a record of the same type can be cast to a record type
+            Type::Custom(name) => {
+                if !matches!(self, Value::Record(r) if r.type_name == name) {
+                    *self = Value::Uninitialized(Type::Custom(name));
+                }
+            }
         }
     }
 
     pub fn modular_div(self, rhs: Self) -> Self {
-        match self {
-            Self::Int(n) => Self::Int(n.modular_div(rhs.to_int())),
-            Self::Z(_) => todo!(),
-            Self::String(ref s) => {
-                // If s is an integer string, convert it to an int
-                if let Ok(n) = s.parse() {
-                    Self::String((Self::Int(n).modular_div(rhs)).to_string())
-                } else {
-                    self / rhs
-                }
-            }
-            Self::Function(_) => Self::Int(Int(127)),
-            Self::Uninitialized(_) => self,
+        self.operation(Operation::ModularDiv, rhs)
+    }
+
+    /// Applies `op` to the values. Anything that fails along the way, e.g. dividing by zero, gives
+    /// 127 (or `i128::MAX`) instead.
+    pub fn operation(self, op: Operation, rhs: Self) -> Self {
+        self.operate(op, rhs, &mut None)
+    }
+
+    /// Applies `op` to the values, or returns what went wrong if anything fails along the way,
+    /// where `operation` would give 127 (or `i128::MAX`) instead.
+    pub fn checked_operation(
+        self,
+        op: Operation,
+        rhs: Self,
+    ) -> Result<Self, Box<RuntimeErrorKind>> {
+        let mut fault = None;
+        let value = self.operate(op, rhs, &mut fault);
+        match fault {
+            Some(fault) => Err(Box::new(fault)),
+            None => Ok(value),
         }
     }
-}
 
-impl Add for Value {
-    type Output = Self;
+    /// The problem with casting the value to `to`, if it is a string that isn't a number being
+    /// cast to an int or a z, which gives 127 (or `i128::MAX`).
+    pub(crate) fn conversion_fault(&self, to: &Type) -> Option<RuntimeErrorKind> {
+        let Value::String(s) = self else {
+            return None;
+        };
 
-    fn add(self, rhs: Self) -> Self::Output {
+        let fails = match to {
+            Type::Int => s.parse::<Int>().is_err(),
+            Type::Z => s.parse::<Z>().is_err(),
+            _ => false,
+        };
+        fails.then(|| RuntimeErrorKind::InvalidConversion {
+            value: self.clone(),
+            to: to.clone(),
+        })
+    }
+
+    /// Applies `op` to the values. The first thing that fails along the way is kept in `fault`.
+    fn operate(self, op: Operation, rhs: Self, fault: &mut Option<RuntimeErrorKind>) -> Self {
+        match op {
+            Operation::Add => self.sum(rhs, fault),
+            Operation::Sub => self.difference(rhs, fault),
+            Operation::Mul => self.product(rhs, fault),
+            Operation::Div => self.quotient(rhs, fault),
+            Operation::ModularDiv => self.modular_quotient(rhs, fault),
+            Operation::Mod => self.remainder(rhs, fault),
+        }
+    }
+
+    /// The value as an int, to be used in an operation.
+    fn int_operand(&self, fault: &mut Option<RuntimeErrorKind>) -> Int {
+        keep_first(fault, || self.conversion_fault(&Type::Int));
+        self.to_int()
+    }
+
+    /// The value as a z, to be used in an operation.
+    fn z_operand(&self, fault: &mut Option<RuntimeErrorKind>) -> Z {
+        keep_first(fault, || self.conversion_fault(&Type::Z));
+        self.to_z()
+    }
+
+    fn sum(self, rhs: Self, fault: &mut Option<RuntimeErrorKind>) -> Self {
         match self {
-            Self::Int(n) => Self::Int(n + rhs.to_int()),
-            Self::Z(z) => Self::Z(z + rhs.to_z()),
+            Self::Int(n) => Self::Int(n + rhs.int_operand(fault)),
+            Self::Z(z) => Self::Z(z + rhs.z_operand(fault)),
             Self::String(mut s) => {
                 match rhs {
                     Self::String(s2) => {
@@ -141,20 +243,17 @@ impl Add for Value {
                 }
             }
             Self::Function(f) => Self::Function(f + rhs.to_function()),
+            Self::Record(r) => r.zip_with(rhs, |a, b| a.sum(b, fault)),
             Self::Uninitialized(_) => Self::Uninitialized(rhs.r#type()),
         }
     }
-}
 
-impl Sub for Value {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self::Output {
+    fn difference(self, rhs: Self, fault: &mut Option<RuntimeErrorKind>) -> Self {
         match self {
-            Self::Int(n) => Self::Int(n - rhs.to_int()),
-            Self::Z(z) => Self::Z(z - rhs.to_z()),
+            Self::Int(n) => Self::Int(n - rhs.int_operand(fault)),
+            Self::Z(z) => Self::Z(z - rhs.z_operand(fault)),
             Self::String(mut s) => match rhs {
-                Self::Int(_) => Self::String(s) - Self::Z(rhs.to_z()),
+                Self::Int(_) => Self::String(s).difference(Self::Z(rhs.to_z()), fault),
                 Self::Z(Z(z)) => {
                     // Remove the last `z` characters from the string
                     for _ in 0..z {
@@ -177,69 +276,254 @@ impl Sub for Value {
                     }
                     Self::Function(f)
                 }
-                _ => Self::Function(f) - Self::Z(rhs.to_z()),
+                _ => Self::Function(f).difference(Self::Z(rhs.to_z()), fault),
             },
+            Self::Record(r) => r.zip_with(rhs, |a, b| a.difference(b, fault)),
             Self::Uninitialized(_) => Self::Uninitialized(self.r#type()),
         }
     }
-}
 
-impl Mul for Value {
-    type Output = Self;
-
-    fn mul(self, rhs: Self) -> Self::Output {
+    fn product(self, rhs: Self, fault: &mut Option<RuntimeErrorKind>) -> Self {
         match self {
-            Self::Int(n) => Self::Int(n * rhs.to_int()),
-            Self::Z(z) => Self::Z(z * rhs.to_z()),
+            Self::Int(n) => Self::Int(n * rhs.int_operand(fault)),
+            Self::Z(z) => Self::Z(z * rhs.z_operand(fault)),
             Self::String(s) => {
                 // If s is an integer string, convert it to an int or z
                 if let Ok(n) = s.parse() {
-                    Self::String((Self::Int(n) * rhs).to_string())
+                    Self::String(Self::Int(n).product(rhs, fault).to_string())
                 } else if let Ok(z) = s.parse() {
-                    Self::String((Self::Z(z) * rhs).to_string())
+                    Self::String(Self::Z(z).product(rhs, fault).to_string())
                 } else {
-                    Self::String(s.repeat(rhs.to_int().0 as usize))
+                    Self::String(s.repeat(rhs.int_operand(fault).0 as usize))
                 }
             }
             Self::Function(f) => Self::Function(f * rhs.to_z()),
+            Self::Record(r) => r.zip_with(rhs, |a, b| a.product(b, fault)),
             Self::Uninitialized(_) => self,
         }
     }
-}
-
-impl Div for Value {
-    type Output = Self;
 
-    fn div(self, rhs: Self) -> Self::Output {
+    fn quotient(self, rhs: Self, fault: &mut Option<RuntimeErrorKind>) -> Self {
         match self {
-            Self::Int(n) => Self::Int(n / rhs.to_int()),
-            Self::Z(z) => Self::Z(z / rhs.to_z()),
-            Self::String(s) => {
+            Self::Int(n) => {
+                let quotient = n.checked_div(rhs.int_operand(fault));
+                Self::Int(quotient.unwrap_or_else(|| division_by_zero(fault, self, rhs, Int(127))))
+            }
+            Self::Z(z) => {
+                let quotient = z.checked_div(rhs.z_operand(fault));
+                Self::Z(
+                    quotient.unwrap_or_else(|| division_by_zero(fault, self, rhs, Z(i128::MAX))),
+                )
+            }
+            Self::String(ref s) => {
                 // If s is an integer string, convert it to an int or z
                 if let Ok(n) = s.parse() {
-                    Self::String((Self::Int(n) / rhs).to_string())
+                    Self::String(Self::Int(n).quotient(rhs, fault).to_string())
                 } else if let Ok(z) = s.parse() {
-                    Self::String((Self::Z(z) / rhs).to_string())
+                    Self::String(Self::Z(z).quotient(rhs, fault).to_string())
                 } else {
-                    let new_len = s.chars().count() / rhs.to_int().0 as usize;
-                    Self::String(s.chars().take(new_len).collect())
+                    // Keep the first 1/n of the characters
+                    let len = s.chars().count();
+                    match len.checked_div(rhs.int_operand(fault).0 as usize) {
+                        Some(new_len) => Self::String(s.chars().take(new_len).collect()),
+                        None => Self::Int(division_by_zero(fault, self, rhs, Int(127))),
+                    }
                 }
             }
             Self::Function(_) => Self::Int(Int(127)),
+            Self::Record(r) => r.zip_with(rhs, |a, b| a.quotient(b, fault)),
             Self::Uninitialized(_) => self,
         }
     }
+
+    fn modular_quotient(self, rhs: Self, fault: &mut Option<RuntimeErrorKind>) -> Self {
+        match self {
+            Self::Int(n) => {
+                let quotient = n.checked_modular_div(rhs.int_operand(fault));
+                Self::Int(quotient.unwrap_or_else(|| division_by_zero(fault, self, rhs, Int(127))))
+            }
+            Self::Z(z) => {
+                let quotient = z.checked_modular_div(rhs.z_operand(fault));
+                Self::Z(
+                    quotient.unwrap_or_else(|| division_by_zero(fault, self, rhs, Z(i128::MAX))),
+                )
+            }
+            Self::String(ref s) => {
+                // If s is an integer string, convert it to an int
+                if let Ok(n) = s.parse() {
+                    Self::String(Self::Int(n).modular_quotient(rhs, fault).to_string())
+                } else {
+                    self.quotient(rhs, fault)
+                }
+            }
+            Self::Function(_) => Self::Int(Int(127)),
+            Self::Record(r) => r.zip_with(rhs, |a, b| a.modular_quotient(b, fault)),
+            Self::Uninitialized(_) => self,
+        }
+    }
+
+    fn remainder(self, rhs: Self, fault: &mut Option<RuntimeErrorKind>) -> Self {
+        match self {
+            Self::Int(n) => {
+                let remainder = n.checked_rem(rhs.int_operand(fault));
+                Self::Int(remainder.unwrap_or_else(|| division_by_zero(fault, self, rhs, Int(127))))
+            }
+            Self::Z(z) => {
+                let remainder = z.checked_rem(rhs.z_operand(fault));
+                Self::Z(
+                    remainder.unwrap_or_else(|| division_by_zero(fault, self, rhs, Z(i128::MAX))),
+                )
+            }
+            Self::String(_) | Self::Uninitialized(_) => {
+                Self::Int(self.int_operand(fault)).remainder(rhs, fault)
+            }
+            Self::Function(_) => Self::Int(Int(127)),
+            Self::Record(r) => r.zip_with(rhs, |a, b| a.remainder(b, fault)),
+        }
+    }
+}
+
+/// Keeps `new` in `fault`, unless an earlier fault is already there.
+fn keep_first(
+    fault: &mut Option<RuntimeErrorKind>,
+    new: impl FnOnce() -> Option<RuntimeErrorKind>,
+) {
+    if fault.is_none() {
+        *fault = new();
+    }
+}
+
+/// Keeps a division by zero of `lhs` by `rhs` in `fault`, and gives `result` as the result of the
+/// division.
+fn division_by_zero<T>(
+    fault: &mut Option<RuntimeErrorKind>,
+    lhs: Value,
+    rhs: Value,
+    result: T,
+) -> T {
+    keep_first(fault, || {
+        Some(RuntimeErrorKind::DivisionByZero { lhs, rhs })
+    });
+    result
+}
+
+impl Add for Value {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.operation(Operation::Add, rhs)
+    }
+}
+
+impl Sub for Value {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.operation(Operation::Sub, rhs)
+    }
+}
+
+impl Mul for Value {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.operation(Operation::Mul, rhs)
+    }
+}
+
+impl Div for Value {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.operation(Operation::Div, rhs)
+    }
 }
 
 impl Rem for Value {
     type Output = Self;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        match self {
-            Self::Int(n) => Self::Int(n % rhs.to_int()),
-            Self::Z(z) => Self::Z(z % rhs.to_z()),
-            Self::String(_) | Self::Uninitialized(_) => Self::Int(self.to_int() % rhs.to_int()),
-            Self::Function(_) => Self::Int(Int(127)),
+        self.operation(Operation::Mod, rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::{
+            error::RuntimeErrorKind,
+            interpreter::InterpreterState,
+            r#type::Type,
+            testing::run_err,
+            value::{int::Int, z::Z, Value},
+        },
+        parser::line::binary_op::Operation,
+    };
+
+    fn fault(lhs: Value, op: Operation, rhs: Value) -> Option<RuntimeErrorKind> {
+        lhs.checked_operation(op, rhs).err().map(|kind| *kind)
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let int = |n| Value::Int(Int(n));
+        for op in [Operation::Div, Operation::ModularDiv, Operation::Mod] {
+            assert_eq!(int(5).operation(op, int(0)), int(127));
+            assert!(matches!(
+                fault(int(5), op, int(0)),
+                Some(RuntimeErrorKind::DivisionByZero { .. })
+            ));
         }
+        assert!(fault(int(5), Operation::Div, int(2)).is_none());
+
+        // An uninitialised value counts as 127 in a remainder
+        let uninitialized = Value::Uninitialized(Type::Int);
+        assert_eq!(
+            uninitialized.clone().operation(Operation::Mod, int(0)),
+            int(127)
+        );
+        assert!(matches!(
+            fault(uninitialized, Operation::Mod, int(0)),
+            Some(RuntimeErrorKind::DivisionByZero { .. })
+        ));
+
+        // A z of 127 divides like zero, rather than indexing past the table of inverses
+        assert!(matches!(
+            fault(int(5), Operation::ModularDiv, Value::Z(Z(127))),
+            Some(RuntimeErrorKind::DivisionByZero { .. })
+        ));
+
+        let string = Value::String("abc".to_owned());
+        assert_eq!(string.clone().operation(Operation::Div, int(0)), int(127));
+        assert!(fault(string, Operation::Div, int(0)).is_some());
+    }
+
+    #[test]
+    fn invalid_conversion() {
+        let abc = Value::String("abc".to_owned());
+        assert_eq!(
+            Value::Int(Int(5)).operation(Operation::Add, abc.clone()),
+            Value::Int(Int(5))
+        );
+        assert!(matches!(
+            fault(Value::Int(Int(5)), Operation::Add, abc.clone()),
+            Some(RuntimeErrorKind::InvalidConversion { to: Type::Int, .. })
+        ));
+        assert!(fault(abc, Operation::Add, Value::Int(Int(5))).is_none());
+    }
+
+    #[test]
+    fn strict_mode_reports_faults() {
+        let source = "print x ÷÷ 0\n";
+        let error = run_err(InterpreterState::capturing().strict(true), source);
+        assert!(matches!(
+            error.kind(),
+            RuntimeErrorKind::DivisionByZero { .. }
+        ));
+
+        let source = "f = fctn\nx = int\nx = f\n";
+        let error = run_err(InterpreterState::capturing().strict(true), source);
+        assert!(matches!(error.kind(), RuntimeErrorKind::EmptyFunction(_)));
+        assert_eq!(error.line_number(), 3);
     }
 }
@@ -3,7 +3,7 @@ use std::{
     str::FromStr,
 };
 
-const MODULUS: u8 = 127;
+pub(crate) const MODULUS: u8 = 127;
 static INVERSES: [u8; 127] = [
     0, 1, 64, 85, 32, 51, 106, 109, 16, 113, 89, 104, 53, 88, 118, 17, 8, 15, 120, 107, 108, 121,
     52, 116, 90, 61, 44, 80, 59, 92, 72, 41, 4, 77, 71, 98, 60, 103, 117, 114, 54, 31, 124, 65, 26,
@@ -44,7 +44,7 @@ impl Div for Int {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        Self(self.0.checked_div(rhs.0).unwrap_or(127))
+        self.checked_div(rhs).unwrap_or(Self(127))
     }
 }
 
@@ -52,21 +52,30 @@ impl Rem for Int {
     type Output = Self;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        if rhs.0 == 0 {
-            Self(127)
-        } else {
-            Self(self.0 % rhs.0)
-        }
+        self.checked_rem(rhs).unwrap_or(Self(127))
     }
 }
 
 impl Int {
     pub fn modular_div(self, rhs: Self) -> Self {
-        if rhs.0 == 0 {
-            Self(127)
-        } else {
-            self * Int(INVERSES[rhs.0 as usize])
-        }
+        self.checked_modular_div(rhs).unwrap_or(Self(127))
+    }
+
+    /// Division, or `None` when dividing by zero.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.0.checked_div(rhs.0).map(Self)
+    }
+
+    /// The remainder, or `None` when dividing by zero.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        self.0.checked_rem(rhs.0).map(Self)
+    }
+
+    /// Modular division, or `None` when dividing by zero. A failed conversion to an int gives 127,
+    /// which is also zero modulo 127.
+    pub fn checked_modular_div(self, rhs: Self) -> Option<Self> {
+        let divisor = rhs.0 % MODULUS;
+        (divisor != 0).then(|| self * Int(INVERSES[divisor as usize]))
     }
 }
 
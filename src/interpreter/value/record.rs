@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use crate::{interpreter::value::Value, parser::line::identifier::Identifier};
+
+/// A value of a record type: the name of the type, and the value of each field in the order they
+/// were declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub(crate) type_name: String,
+    pub(crate) fields: Vec<(Identifier, Value)>,
+}
+
+impl Record {
+    pub fn field(&self, name: &Identifier) -> Option<&Value> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    pub fn field_mut(&mut self, name: &Identifier) -> Option<&mut Value> {
+        self.fields
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+    }
+
+    /// Applies `op` to each field. If `rhs` is a record of the same type, each field is combined
+    /// with the same field of `rhs`, and otherwise each field is combined with the whole of `rhs`.
+    pub(crate) fn zip_with(self, rhs: Value, mut op: impl FnMut(Value, Value) -> Value) -> Value {
+        let fields = match rhs {
+            Value::Record(rhs) if rhs.type_name == self.type_name => self
+                .fields
+                .into_iter()
+                .zip(rhs.fields)
+                .map(|((name, a), (_, b))| (name, op(a, b)))
+                .collect(),
+            rhs => self
+                .fields
+                .into_iter()
+                .map(|(name, a)| (name, op(a, rhs.clone())))
+                .collect(),
+        };
+
+        Value::Record(Self {
+            type_name: self.type_name,
+            fields,
+        })
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{ ", self.type_name)?;
+        for (i, (name, value)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}: {value}")?;
+        }
+        f.write_str(" }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::testing::run;
+
+    const TYPES: &str = "point = record x int, y z\nline = record from point, to point\n";
+
+    #[test]
+    fn fields_are_read_and_written() {
+        let source =
+            "p = point\nprint p\np.x = 3\np.y = -1z\np.x = p.x + 130\nprint p.x\nprint p.y\n";
+        assert_eq!(
+            run(&format!("{TYPES}{source}")),
+            "point { x: nothing, y: nothing }\n6\n-1\n"
+        );
+    }
+
+    #[test]
+    fn records_can_contain_records() {
+        let source = "l = line\nl.to.x = 5\nprint l.to.x\nprint l\np = point\np.y = 2z\nl.from = p\nprint l.from.y\n";
+        assert_eq!(
+            run(&format!("{TYPES}{source}")),
+            "5\nline { from: nothing, to: point { x: 5, y: nothing } }\n2\n"
+        );
+    }
+
+    #[test]
+    fn only_records_of_the_same_type_can_be_assigned() {
+        let source = "p = point\np.x = 3\nq = point\nq = p\nprint q\nl = line\nl = p\nprint l\nl = 5\nprint l\n";
+        assert_eq!(
+            run(&format!("{TYPES}{source}")),
+            "point { x: 3, y: nothing }\nnothing\nnothing\n"
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_left_alone() {
+        let source = "p = point\np.x = 3\np.z = 5\nprint p\nprint p.z\n";
+        assert_eq!(
+            run(&format!("{TYPES}{source}")),
+            "point { x: 3, y: nothing }\nnothing\n"
+        );
+    }
+}
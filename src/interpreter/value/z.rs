@@ -4,6 +4,8 @@ use std::{
     str::FromStr,
 };
 
+use crate::interpreter::value::int::{Int, MODULUS};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Z(pub(crate) i128);
 
@@ -35,7 +37,7 @@ impl Div for Z {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        Self(self.0.checked_div(rhs.0).unwrap_or(i128::MAX))
+        self.checked_div(rhs).unwrap_or(Self(i128::MAX))
     }
 }
 
@@ -43,7 +45,34 @@ impl Rem for Z {
     type Output = Self;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        Self(self.0.checked_rem(rhs.0).unwrap_or(i128::MAX))
+        self.checked_rem(rhs).unwrap_or(Self(i128::MAX))
+    }
+}
+
+impl Z {
+    /// Division modulo 127, like for ints. Both sides are first reduced to their residues (so a
+    /// negative z counts from the top, e.g. -1 is 126), and the result is a z from 0 to 126.
+    /// Dividing by a multiple of 127 gives `i128::MAX`, the same as dividing by zero.
+    pub fn modular_div(self, rhs: Self) -> Self {
+        self.checked_modular_div(rhs).unwrap_or(Self(i128::MAX))
+    }
+
+    /// Division, or `None` when dividing by zero (or when the result doesn't fit).
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.0.checked_div(rhs.0).map(Self)
+    }
+
+    /// The remainder, or `None` when dividing by zero (or when the result doesn't fit).
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        self.0.checked_rem(rhs.0).map(Self)
+    }
+
+    /// Modular division, or `None` when dividing by a multiple of 127.
+    pub fn checked_modular_div(self, rhs: Self) -> Option<Self> {
+        let residue = |z: Self| Int(z.0.rem_euclid(MODULUS as i128) as u8);
+        residue(self)
+            .checked_modular_div(residue(rhs))
+            .map(|n| Self(n.0 as i128))
     }
 }
 
@@ -54,3 +83,25 @@ impl FromStr for Z {
         s.parse::<i128>().map(Z)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::value::z::Z;
+
+    #[test]
+    fn modular_div_uses_residues() {
+        assert_eq!(Z(6).modular_div(Z(3)), Z(2));
+        // -5 is 122, and 3 * 83 = 249 = 122 (mod 127)
+        assert_eq!(Z(-5).modular_div(Z(3)), Z(83));
+        assert_eq!(Z(5).modular_div(Z(-1)), Z(122));
+        // 1000 is 111, 130 is 3, and 3 * 37 = 111
+        assert_eq!(Z(1000).modular_div(Z(130)), Z(37));
+    }
+
+    #[test]
+    fn modular_div_by_multiple_of_modulus() {
+        assert_eq!(Z(5).modular_div(Z(0)), Z(i128::MAX));
+        assert_eq!(Z(5).modular_div(Z(127)), Z(i128::MAX));
+        assert_eq!(Z(5).modular_div(Z(-254)), Z(i128::MAX));
+    }
+}
@@ -1,5 +1,7 @@
 #![feature(get_many_mut)]
 #![feature(let_chains)]
 
+pub mod format;
 pub mod interpreter;
 pub mod parser;
+pub mod renumber;
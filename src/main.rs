@@ -1,18 +1,175 @@
 use std::{fs::File, io::Read};
 
-use code::{interpreter::interpreter::InterpreterState, parser::program::program};
+use code::{
+    format::format_source,
+    interpreter::interpreter::InterpreterState,
+    parser::program::{program, program_recovering, Program},
+    renumber::{delete_lines, insert_empty_lines, move_lines},
+};
 
 fn main() {
-    let Some(file_name) = std::env::args().nth(1) else { return };
-    let source_code = {
-        let mut string = String::new();
-        File::open(file_name)
-            .expect("File not found")
-            .read_to_string(&mut string)
-            .expect("Failed to read file");
-        string
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        ["check", file_name] => check(file_name),
+        ["fmt", "--check", file_names @ ..] => fmt(file_names, true),
+        ["fmt", file_names @ ..] => fmt(file_names, false),
+        ["renumber", args @ ..] => renumber(args),
+        [file_name] => run(file_name),
+        _ => usage(),
+    }
+}
+
+fn read_source(file_name: &str) -> String {
+    let mut string = String::new();
+    File::open(file_name)
+        .expect("File not found")
+        .read_to_string(&mut string)
+        .expect("Failed to read file");
+    string
+}
+
+/// Parses the file, or reports the parse errors and exits.
+fn read_program(file_name: &str) -> Program {
+    let source_code = read_source(file_name);
+    match program(&source_code) {
+        Ok(program) => program,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{error}\n");
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The size of the stack that programs run on. Each function call takes up some of it, so this is
+/// large enough for functions that call themselves thousands of times.
+const RUN_STACK_SIZE: usize = 1 << 30;
+
+fn run(file_name: &str) {
+    let program = read_program(file_name);
+    let result = std::thread::Builder::new()
+        .stack_size(RUN_STACK_SIZE)
+        .spawn(move || InterpreterState::new().run(program).map(|_| ()))
+        .expect("Failed to start the interpreter thread")
+        .join()
+        .expect("The interpreter thread panicked");
+    if let Err(error) = result {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
+
+/// Reports every line of the program that fails to parse, without running it.
+fn check(file_name: &str) {
+    let source_code = read_source(file_name);
+    let program = program_recovering(&source_code);
+
+    let mut num_errors = 0;
+    for error in program.errors() {
+        eprintln!("{error}\n");
+        num_errors += 1;
+    }
+
+    if num_errors > 0 {
+        eprintln!("error: {num_errors} line(s) failed to parse");
+        std::process::exit(1);
+    }
+}
+
+/// Formats each file in place. With `--check`, the files are left alone, and the exit code is
+/// non-zero if any of them would be changed.
+fn fmt(file_names: &[&str], check: bool) {
+    let mut failed = false;
+
+    for file_name in file_names {
+        let source_code = read_source(file_name);
+        let formatted = match format_source(&source_code) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("{file_name}: {error}\n");
+                }
+                failed = true;
+                continue;
+            }
+        };
+
+        if formatted == source_code {
+            continue;
+        }
+
+        if check {
+            // Report the first line that differs
+            let line_number = source_code
+                .lines()
+                .zip(formatted.lines())
+                .position(|(a, b)| a != b)
+                .map_or_else(|| source_code.lines().count(), |i| i + 1);
+            println!("{file_name}:{line_number}: not formatted");
+            failed = true;
+        } else {
+            std::fs::write(file_name, formatted).expect("Failed to write file");
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Inserts, deletes or moves lines of a file in place, keeping `come from` targets pointing at the
+/// same lines.
+///
+/// - `renumber insert <file> <line> [<count>]` inserts empty lines before `line`
+/// - `renumber delete <file> <first> [<last>]` deletes lines `first` to `last`
+/// - `renumber move <file> <first> <last> <to>` moves lines `first` to `last` to before line `to`
+fn renumber(args: &[&str]) {
+    let Some(&file_name) = args.get(1) else {
+        usage();
+    };
+    let line = |s: &str| -> usize {
+        s.parse().unwrap_or_else(|_| {
+            eprintln!("error: invalid line number {s}");
+            std::process::exit(1);
+        })
     };
-    let program = program(&source_code);
-    let mut interpreter = InterpreterState::new();
-    interpreter.run(program);
+
+    let mut program = read_program(file_name);
+    let result = match args {
+        ["insert", _, at] => insert_empty_lines(&mut program, line(at), 1),
+        ["insert", _, at, count] => insert_empty_lines(&mut program, line(at), line(count)),
+        ["delete", _, first, rest @ ..] if rest.len() <= 1 => {
+            let last = rest.first().map_or(line(first), |l| line(l));
+            delete_lines(&mut program, line(first)..=last)
+        }
+        ["move", _, first, last, to] => {
+            move_lines(&mut program, line(first)..=line(last), line(to))
+        }
+        _ => usage(),
+    };
+
+    if let Err(error) = result {
+        for line in error.to_string().lines() {
+            eprintln!("error: {line}");
+        }
+        std::process::exit(1);
+    }
+
+    std::fs::write(file_name, program.to_string()).expect("Failed to write file");
+}
+
+/// Reports how to use the binary, and exits.
+fn usage() -> ! {
+    eprintln!(
+        "usage:
+    code <file>
+    code check <file>
+    code fmt [--check] <file>...
+    code renumber insert <file> <line> [<count>]
+    code renumber delete <file> <first> [<last>]
+    code renumber move <file> <first> <last> <to>"
+    );
+    std::process::exit(2);
 }
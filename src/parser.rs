@@ -1,2 +1,4 @@
+pub mod error;
 pub mod line;
 pub mod program;
+pub mod span;
@@ -0,0 +1,151 @@
+use std::fmt::Display;
+
+use nom::error::{VerboseError, VerboseErrorKind};
+
+use crate::parser::span::{Input, Span};
+
+/// An error produced when a line of a program could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub(crate) line_number: usize,
+    pub(crate) column: usize,
+    /// The line as it was written. For a statement written over several lines, this is all of
+    /// them.
+    pub(crate) line: String,
+    /// The physical line that the error is on, which is shown under the message.
+    pub(crate) context: String,
+    pub(crate) text: String,
+    pub(crate) parser: Option<&'static str>,
+    pub(crate) message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: Input, error: nom::Err<VerboseError<Input>>) -> Self {
+        let (text, parser) = match &error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                // The first entry is where the innermost parser failed, and the first context is
+                // the name of that parser. There is no context if no parser got anywhere.
+                let parser = e.errors.iter().find_map(|(_, kind)| match kind {
+                    VerboseErrorKind::Context(name) => Some(*name),
+                    _ => None,
+                });
+                (e.errors[0].0, parser)
+            }
+            nom::Err::Incomplete(_) => (line, None),
+        };
+        let message = match parser {
+            Some(parser) => format!("parse error in {parser}"),
+            None => "unrecognised line".to_owned(),
+        };
+
+        Self {
+            line_number: text.location_line() as usize,
+            column: text.get_utf8_column(),
+            line: line.fragment().to_string(),
+            context: physical_line(line, text.location_offset()).to_owned(),
+            text: text.fragment().to_string(),
+            parser,
+            message,
+        }
+    }
+
+    /// An error about the part of `line` covered by `span`, which the line parsers accepted but
+    /// is nonetheless invalid (e.g. an undefined label).
+    pub(crate) fn at(line: Input, span: Span, parser: &'static str, message: String) -> Self {
+        let offset = line.location_offset();
+
+        Self {
+            line_number: span.line as usize,
+            column: span.column,
+            line: line.fragment().to_string(),
+            context: physical_line(line, span.start).to_owned(),
+            text: line[span.start - offset..span.end - offset].to_owned(),
+            parser: Some(parser),
+            message,
+        }
+    }
+
+    /// The 1-based line number of the (physical) line that the error is on.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// The 1-based column (in characters) where the offending text starts.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The part of the line that could not be parsed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The name of the parser that rejected the line, e.g. `equality` or `literal`, or `None` if
+    /// the line isn't anything that a parser recognised.
+    pub fn parser(&self) -> Option<&'static str> {
+        self.parser
+    }
+
+    /// A description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let line_number = self.line_number.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let padding = " ".repeat(self.column - 1);
+        let carets = "^".repeat(self.text.chars().count().max(1));
+
+        writeln!(f, "error: {}", self.message)?;
+        writeln!(f, "{gutter}--> {}:{}", self.line_number, self.column)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line_number} | {}", self.context)?;
+        write!(f, "{gutter} | {padding}{carets}")
+    }
+}
+
+/// The physical line of `line` (which may be a statement written over several lines) that contains
+/// the byte at `offset` in the program source.
+fn physical_line<'a>(line: Input<'a>, offset: usize) -> &'a str {
+    let source: &'a str = line.fragment();
+    let offset = (offset - line.location_offset()).min(source.len());
+    let start = source[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |idx| offset + idx);
+    source[start..end].trim_end_matches('\r')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::program::program;
+
+    #[test]
+    fn unrecognised_line_names_no_parser() {
+        for source in ["%%%\n", "\tx = 1\n"] {
+            let errors = program(source).unwrap_err();
+            assert_eq!(errors[0].parser(), None);
+            assert_eq!(errors[0].message(), "unrecognised line");
+            assert_eq!(errors[0].column(), 1);
+        }
+    }
+
+    #[test]
+    fn error_in_multi_line_string_is_on_its_physical_line() {
+        let errors = program("x = 1\ns = the string a\\\nbc\\q\n").unwrap_err();
+        assert_eq!(errors[0].line_number(), 3);
+        assert_eq!(errors[0].column(), 3);
+        assert_eq!(errors[0].context, "bc\\q");
+    }
+
+    #[test]
+    fn error_past_the_start_names_the_parser() {
+        let errors = program("x = 5\nprint %%\n").unwrap_err();
+        assert_eq!(errors[0].line_number(), 2);
+        assert_eq!(errors[0].column(), 7);
+        assert_eq!(errors[0].parser(), Some("print"));
+    }
+}
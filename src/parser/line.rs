@@ -1,12 +1,30 @@
 // Things that are parsed from a single line of code (or part of a line).
 
+use nom::error::VerboseError;
+
+use crate::parser::span::Input;
+
 pub mod binary_op;
 pub mod bracketed_identifier;
 pub mod come_from;
+pub mod comment;
+pub mod comparison;
+pub mod r#else;
 pub mod equality;
 pub mod expression;
+pub mod group;
 pub mod identifier;
 pub mod indented_expression;
+pub mod label;
+mod left_chain;
+pub mod lexer;
 pub mod literal;
+pub mod logical;
 mod parser_chain;
 pub mod print;
+pub mod read_input;
+pub mod record_type;
+
+/// The result type of all of the line parsers. Errors carry the name of the parser that rejected
+/// the input (see `parser_chain!`), which is used when reporting parse errors.
+pub type LineResult<'a, O> = nom::IResult<Input<'a>, O, VerboseError<Input<'a>>>;
@@ -1,11 +1,20 @@
 use nom::{
-    error::{Error, ErrorKind},
-    IResult,
+    error::{ErrorKind, ParseError, VerboseError},
+    Slice,
 };
-use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-use crate::parser::line::expression::{expression, Expression};
+use crate::{
+    left_chain,
+    parser::{
+        line::{
+            expression::{expression, Expression},
+            lexer::{tokens, Operator, Token},
+            LineResult,
+        },
+        span::{Input, Span},
+    },
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
 pub enum Operation {
@@ -30,43 +39,131 @@ impl Operation {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// One operation in a chain of binary operations (see `left_chain!`).
+#[derive(Debug)]
 pub struct BinaryOp {
     pub(crate) lhs: Box<Expression>,
     pub(crate) rhs: Box<Expression>,
     pub(crate) op: Operation,
+    pub(crate) span: Span,
 }
 
-pub fn binary_op(input: &str) -> IResult<&str, BinaryOp> {
-    // Find the operation that appears latest in the string. This will make it so that all
-    // binary operations will be interpreted from left to right.
-    let Some((op, lhs, rhs)) = Operation::iter()
-        .filter_map(|op| {
-            input
-                .rsplit_once(&format!(" {} ", op.symbol()))
-                .map(|(lhs, rhs)| (op, lhs, rhs))
-        })
-        .max_by_key(|(_, lhs, _)| lhs.len())
-    else {
-        return Err(nom::Err::Failure(Error::new(input, ErrorKind::Fail)));
-    };
+impl BinaryOp {
+    pub fn span(&self) -> Span {
+        self.span
+    }
 
-    let (rest, lhs) = expression(lhs, true)?;
-    if !rest.is_empty() {
-        return Err(nom::Err::Failure(Error::new(input, ErrorKind::Fail)));
+    /// The LHS, which is the rest of the chain before this operation.
+    pub fn lhs(&self) -> &Expression {
+        &self.lhs
     }
 
-    let (rest, rhs) = expression(rhs, true)?;
-    if !rest.is_empty() {
-        return Err(nom::Err::Failure(Error::new(input, ErrorKind::Fail)));
+    pub fn rhs(&self) -> &Expression {
+        &self.rhs
+    }
+
+    pub fn op(&self) -> Operation {
+        self.op
     }
+}
 
-    Ok((
-        "",
-        BinaryOp {
+left_chain!(BinaryOp, Expression::BinaryOp, op);
+
+/// A chain of binary operations, e.g. `a + b * c`. The operations are applied from left to right,
+/// regardless of which operators they are, so this is `(a + b) * c`.
+pub fn binary_op(input: Input) -> LineResult<BinaryOp> {
+    let tokens = tokens(input);
+    // Comparisons and connectives bind less tightly, so they can't be part of the chain
+    let has_other_operator = tokens.iter().any(|t| {
+        matches!(
+            t,
+            Token::Operator(Operator::Comparator(_) | Operator::Connective(_), _)
+        )
+    });
+    if tokens.len() == 1 || has_other_operator {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Fail,
+        )));
+    }
+
+    let mut tokens = tokens.into_iter();
+    let Some(Token::Operand(first)) = tokens.next() else {
+        unreachable!()
+    };
+    let mut lhs = operand(first)?;
+    let mut rest = first.slice(first.len()..);
+
+    while let (Some(Token::Operator(Operator::Operation(op), _)), Some(Token::Operand(segment))) =
+        (tokens.next(), tokens.next())
+    {
+        let rhs = operand(segment)?;
+        rest = segment.slice(segment.len()..);
+        lhs = Expression::BinaryOp(BinaryOp {
             lhs: Box::new(lhs),
             rhs: Box::new(rhs),
             op,
-        },
-    ))
+            span: Span::new(input, rest),
+        });
+    }
+
+    let Expression::BinaryOp(op) = lhs else {
+        unreachable!()
+    };
+    Ok((rest, op))
+}
+
+/// One of the operands of a chain of binary operations, which can be any expression that doesn't
+/// contain an operator.
+fn operand(input: Input) -> Result<Expression, nom::Err<VerboseError<Input>>> {
+    let (rest, expr) = expression(input, true)?;
+    if !rest.is_empty() {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Fail,
+        )));
+    }
+
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{interpreter::testing::run, parser::program::program};
+
+    #[test]
+    fn long_chains_dont_recurse() {
+        let terms = 50_000;
+        let source = format!("x = 1{}\nprint x\n", " + 1".repeat(terms - 1));
+
+        let parsed = program(&source).unwrap();
+        assert_eq!(parsed.to_string(), source);
+        assert_eq!(parsed.clone(), parsed);
+        assert_eq!(run(&source), format!("{}\n", terms % 127));
+    }
+
+    #[test]
+    fn long_comparison_chains_dont_recurse() {
+        let terms = 20_000;
+        let source = format!("x = 1\nx < 2{}\n print 1\n", " < 2".repeat(terms - 2));
+
+        let parsed = program(&source).unwrap();
+        assert_eq!(parsed.to_string(), source);
+        assert_eq!(parsed.clone(), parsed);
+        assert_eq!(run(&source), "1\n");
+    }
+
+    #[test]
+    fn long_connective_chains_dont_recurse() {
+        let terms = 20_000;
+        let source = format!(
+            "x = 2\nx = 3{} or x = 2\n print 2\n",
+            " or x = 3 and x = 2".repeat(terms / 2 - 1)
+        );
+
+        let parsed = program(&source).unwrap();
+        assert_eq!(parsed.to_string(), source);
+        assert_eq!(parsed.clone(), parsed);
+        assert_eq!(run(&source), "2\n");
+    }
 }
@@ -1,27 +1,73 @@
+use std::fmt::Display;
+
 use nom::{
     character::complete::char,
-    multi::{count, many0_count},
-    IResult,
+    multi::{count, many0, many0_count},
+    sequence::preceded,
 };
 
-use crate::parser::line::identifier::{identifier, Identifier};
+use crate::parser::{
+    line::{
+        identifier::{identifier, Identifier},
+        LineResult,
+    },
+    span::{Input, Span},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BracketedIdentifier {
     pub(crate) identifier: Identifier,
     pub(crate) num_brackets: u32,
+    /// The fields accessed after the brackets, e.g. `x` and `y` in `(p).x.y`.
+    pub(crate) fields: Vec<Identifier>,
+    pub(crate) span: Span,
+}
+
+impl BracketedIdentifier {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn identifier(&self) -> &Identifier {
+        &self.identifier
+    }
+
+    /// The number of pairs of brackets around the identifier.
+    pub fn num_brackets(&self) -> u32 {
+        self.num_brackets
+    }
+
+    /// The fields accessed after the brackets, e.g. `x` and `y` in `(p).x.y`.
+    pub fn fields(&self) -> &[Identifier] {
+        &self.fields
+    }
+}
+
+impl Display for BracketedIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let n = self.num_brackets as usize;
+        write!(f, "{}{}{}", "(".repeat(n), self.identifier, ")".repeat(n))?;
+        for field in &self.fields {
+            write!(f, ".{field}")?;
+        }
+        Ok(())
+    }
 }
 
-pub fn bracketed_identifier(input: &str) -> IResult<&str, BracketedIdentifier> {
+pub fn bracketed_identifier(input: Input) -> LineResult<BracketedIdentifier> {
+    let start = input;
     let (input, cnt_left) = many0_count(char('('))(input)?;
     let (input, ident) = identifier(input)?;
     let (input, _) = count(char(')'), cnt_left)(input)?;
+    let (input, fields) = many0(preceded(char('.'), identifier))(input)?;
 
     Ok((
         input,
         BracketedIdentifier {
             identifier: ident,
             num_brackets: cnt_left as u32,
+            fields,
+            span: Span::new(start, input),
         },
     ))
 }
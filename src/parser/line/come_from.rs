@@ -1,15 +1,79 @@
-use nom::{bytes::streaming::tag, IResult};
+use std::fmt::Display;
 
-use crate::parser::line::literal::{integer, IntegerLit};
+use nom::{branch::alt, bytes::streaming::tag, combinator::map};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::parser::{
+    line::{
+        label::{label, Label},
+        literal::{unsigned_integer, IntegerLit},
+        LineResult,
+    },
+    span::{Input, Span},
+};
+
+#[derive(Debug, Clone, Eq)]
 pub struct ComeFrom {
     pub(crate) line_number: IntegerLit,
+    /// The label that the line was referred to by, if any. `line_number` is filled in from the
+    /// label when the whole program is parsed.
+    pub(crate) label: Option<Label>,
+    pub(crate) span: Span,
+}
+
+impl ComeFrom {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The line that is come from. For a label, this is the line that the label is defined on.
+    pub fn line_number(&self) -> &IntegerLit {
+        &self.line_number
+    }
+
+    /// The label that the line was referred to by, if any.
+    pub fn label(&self) -> Option<&Label> {
+        self.label.as_ref()
+    }
+}
+
+/// Only the line numbers are compared, so a `come from` using a label is equal to one using the
+/// line number of that label.
+impl PartialEq for ComeFrom {
+    fn eq(&self, other: &Self) -> bool {
+        self.line_number == other.line_number
+    }
+}
+
+impl Display for ComeFrom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "come from {label}"),
+            None => write!(f, "come from {}", self.line_number),
+        }
+    }
 }
 
-pub fn come_from(input: &str) -> IResult<&str, ComeFrom> {
+pub fn come_from(input: Input) -> LineResult<ComeFrom> {
+    let start = input;
     let (input, _) = tag("come from ")(input)?;
-    let (input, line_number) = integer(input)?;
+    let (input, (line_number, label)) = alt((
+        map(unsigned_integer, |n| (n, None)),
+        map(label, |l| {
+            let n = IntegerLit {
+                value: 0,
+                suffix: None,
+                span: l.span,
+            };
+            (n, Some(l))
+        }),
+    ))(input)?;
 
-    Ok((input, ComeFrom { line_number }))
+    Ok((
+        input,
+        ComeFrom {
+            line_number,
+            label,
+            span: Span::new(start, input),
+        },
+    ))
 }
@@ -0,0 +1,99 @@
+use std::fmt::Display;
+
+use nom::{character::complete::char, Slice};
+
+use crate::parser::{
+    line::LineResult,
+    span::{Input, Span},
+};
+
+/// A comment, which runs from a `#` to the end of the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub(crate) text: String,
+    pub(crate) span: Span,
+}
+
+impl Comment {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The text after the `#`.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for Comment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.text)
+    }
+}
+
+pub fn comment(input: Input) -> LineResult<Comment> {
+    let start = input;
+    let (input, _) = char('#')(input)?;
+    let rest = input.slice(input.len()..);
+
+    Ok((
+        rest,
+        Comment {
+            text: input.fragment().to_string(),
+            span: Span::new(start, rest),
+        },
+    ))
+}
+
+/// Splits a line into its code and the comment at the end of it, if there is one.
+///
+/// The comment starts at the first `#` that isn't part of a string literal. String literals run to
+/// the end of the line, so a `#` anywhere after `the string ` is part of the string. A single
+/// space before the `#` is part of the comment marker, so `x = 1 # one` has the code `x = 1`.
+pub fn split_comment(input: Input) -> LineResult<(Input, Option<Comment>)> {
+    let string_start = input.find("the string ").unwrap_or(input.len());
+    let Some(idx) = input[..string_start].find('#') else {
+        return Ok((input.slice(input.len()..), (input, None)));
+    };
+
+    let (rest, comment) = comment(input.slice(idx..))?;
+    let code_end = if input[..idx].ends_with(' ') {
+        idx - 1
+    } else {
+        idx
+    };
+
+    Ok((rest, (input.slice(..code_end), Some(comment))))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format::format_source, interpreter::testing::run, parser::program::program};
+
+    #[test]
+    fn hashes_in_strings_are_not_comments() {
+        let source = "print the string a # b\n";
+        let parsed = program(source).unwrap();
+        assert!(parsed.lines()[0].comment().is_none());
+        assert_eq!(run(source), "a # b\n");
+    }
+
+    #[test]
+    fn comments_are_kept_when_printing() {
+        let source = "x = 1 # one\n# on its own line\nx = 1\n  print x # indented\n";
+        let parsed = program(source).unwrap();
+        assert_eq!(parsed.lines()[0].comment().unwrap().text(), " one");
+        assert_eq!(parsed.to_string(), source);
+        assert_eq!(
+            format_source(source).unwrap(),
+            "x = 1 # one\n# on its own line\nx = 1\n print x # indented\n"
+        );
+    }
+
+    #[test]
+    fn comment_lines_keep_their_line_numbers() {
+        // Line 5 comes from line 3, so `print 3` is skipped
+        let source = "print 1\n# skip ahead\nprint 2\nprint 3\ncome from 3\nprint 4\n";
+        assert_eq!(run(source), "1\n2\n4\n");
+    }
+}
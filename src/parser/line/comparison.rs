@@ -0,0 +1,162 @@
+use nom::error::{ErrorKind, ParseError, VerboseError};
+use strum_macros::EnumIter;
+
+use crate::{
+    left_chain,
+    parser::{
+        line::{
+            expression::{expression, Expression},
+            lexer::{split_at, tokens, Operator, Token},
+            LineResult,
+        },
+        span::{Input, Span},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Comparator {
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    NotEqual,
+}
+
+impl Comparator {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Less => "<",
+            Self::Greater => ">",
+            Self::LessEqual => "≤",
+            Self::GreaterEqual => "≥",
+            Self::NotEqual => "≠",
+        }
+    }
+}
+
+/// A comparison between two values, e.g. `a < b + 1`, which is 1 if it holds and 0 otherwise. It is
+/// one link in a chain of comparisons (see `left_chain!`).
+#[derive(Debug)]
+pub struct Comparison {
+    pub(crate) lhs: Box<Expression>,
+    pub(crate) rhs: Box<Expression>,
+    pub(crate) comparator: Comparator,
+    pub(crate) span: Span,
+}
+
+impl Comparison {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The LHS, which is the rest of the chain before this comparison.
+    pub fn lhs(&self) -> &Expression {
+        &self.lhs
+    }
+
+    pub fn rhs(&self) -> &Expression {
+        &self.rhs
+    }
+
+    pub fn comparator(&self) -> Comparator {
+        self.comparator
+    }
+}
+
+left_chain!(Comparison, Expression::Comparison, comparator);
+
+/// A comparison, which binds less tightly than the binary operations on either side of it (but
+/// more tightly than connectives). Several comparisons in a row are applied from left to right,
+/// like binary operations.
+pub fn comparison(input: Input) -> LineResult<Comparison> {
+    let tokens = tokens(input);
+    let has_connective = tokens
+        .iter()
+        .any(|t| matches!(t, Token::Operator(Operator::Connective(_), _)));
+    let parts = split_at(input, tokens, |op| matches!(op, Operator::Comparator(_)));
+    if has_connective || parts.len() == 1 {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Fail,
+        )));
+    }
+
+    let mut parts = parts.into_iter();
+    let (_, first) = parts.next().unwrap();
+    let (mut rest, mut lhs) = expression(first, true)?;
+    for (before, part) in parts {
+        let Some(Operator::Comparator(comparator)) = before else {
+            unreachable!()
+        };
+        let rhs;
+        (rest, rhs) = expression(part, true)?;
+        lhs = Expression::Comparison(Comparison {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            comparator,
+            span: Span::new(input, rest),
+        });
+    }
+
+    let Expression::Comparison(comparison) = lhs else {
+        unreachable!()
+    };
+    Ok((rest, comparison))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::testing::run;
+
+    #[test]
+    fn comparators_on_ints_and_zs() {
+        let source = "\
+print 1 < 2
+print 2 < 1
+print 2 > 1
+print 2 ≤ 2
+print 3 ≤ 2
+print 2 ≥ 3
+print 2 ≥ 2
+print 2 ≠ 2
+print 2 ≠ 3
+print -5 < 3z
+print 200 > 1000
+print -5 ≥ -5
+";
+        assert_eq!(run(source), "1\n0\n1\n1\n0\n0\n1\n0\n1\n1\n0\n1\n");
+    }
+
+    #[test]
+    fn comparators_on_strings() {
+        let source = "\
+a = the string apple
+b = the string banana
+print a < b
+print b ≤ a
+print a ≠ b
+print a ≥ a
+print b > a
+";
+        assert_eq!(run(source), "1\n0\n1\n1\n1\n");
+    }
+
+    #[test]
+    fn comparators_cast_the_lhs_to_the_type_of_the_rhs() {
+        let source = "\
+a = the string apple
+s = the string 10
+n = 3
+print 5 < 200
+print -1 > 5
+print n < a
+print a < 5
+print s > 9
+print 9 < s
+print 5 ≠ 5z
+";
+        // -1 isn't an int, so it is 127 as one. `s > 9` compares numbers, but `9 < s` compares
+        // strings.
+        assert_eq!(run(source), "1\n1\n1\n0\n1\n0\n0\n");
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+use nom::bytes::complete::tag;
+
+use crate::parser::{
+    line::LineResult,
+    span::{Input, Span},
+};
+
+/// An `else` line. Its indented block runs only when the guard that it follows (at the same
+/// indentation) is false. Reaching the `else` line in any other way skips its block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Else {
+    pub(crate) span: Span,
+}
+
+impl Else {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Display for Else {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("else")
+    }
+}
+
+pub fn r#else(input: Input) -> LineResult<Else> {
+    let start = input;
+    let (input, _) = tag("else")(input)?;
+
+    Ok((
+        input,
+        Else {
+            span: Span::new(start, input),
+        },
+    ))
+}
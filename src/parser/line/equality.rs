@@ -1,39 +1,78 @@
+use std::fmt::Display;
+
 use nom::{
-    error::{Error, ErrorKind},
-    IResult,
+    error::{ErrorKind, ParseError, VerboseError},
+    Slice,
 };
 
-use crate::parser::line::{
-    bracketed_identifier::{bracketed_identifier, BracketedIdentifier},
-    indented_expression::{indented_expression, IndentedExpression},
+use crate::parser::{
+    line::{
+        bracketed_identifier::{bracketed_identifier, BracketedIdentifier},
+        indented_expression::{indented_expression, IndentedExpression},
+        LineResult,
+    },
+    span::{Input, Span},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Equality {
     pub(crate) lhs: BracketedIdentifier,
     pub(crate) rhs: Box<IndentedExpression>,
+    pub(crate) span: Span,
+}
+
+impl Equality {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The variable that is assigned to (or compared, in a conditional).
+    pub fn lhs(&self) -> &BracketedIdentifier {
+        &self.lhs
+    }
+
+    /// The value that is assigned (or compared).
+    pub fn rhs(&self) -> &IndentedExpression {
+        &self.rhs
+    }
+}
+
+impl Display for Equality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.lhs, self.rhs)
+    }
 }
 
-pub fn equality(input: &str) -> IResult<&str, Equality> {
-    let Some((lhs, rhs)) = input.split_once(" = ") else {
-        return Err(nom::Err::Failure(Error::new(input, ErrorKind::Fail)));
+pub fn equality(input: Input) -> LineResult<Equality> {
+    let Some(idx) = input.find(" = ") else {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Fail,
+        )));
     };
 
-    let (result, lhs) = bracketed_identifier(lhs)?;
+    let (result, lhs) = bracketed_identifier(input.slice(..idx))?;
     if !result.is_empty() {
-        return Err(nom::Err::Failure(Error::new(result, ErrorKind::Fail)));
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            result,
+            ErrorKind::Fail,
+        )));
     }
 
-    let (result, rhs) = indented_expression(rhs, true)?;
+    let (result, rhs) = indented_expression(input.slice(idx + 3..), true)?;
     if !result.is_empty() {
-        return Err(nom::Err::Failure(Error::new(result, ErrorKind::Fail)));
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            result,
+            ErrorKind::Fail,
+        )));
     }
 
     Ok((
-        "",
+        result,
         Equality {
             lhs,
             rhs: Box::new(rhs),
+            span: Span::new(input, result),
         },
     ))
 }
@@ -1,16 +1,25 @@
-use nom::{
-    error::{Error, ErrorKind},
-    Err, IResult,
-};
+use std::fmt::Display;
 
 use crate::{
-    parser::line::{
-        binary_op::{binary_op, BinaryOp},
-        come_from::{come_from, ComeFrom},
-        equality::{equality, Equality},
-        identifier::{identifier, Identifier},
-        literal::{literal, Literal},
-        print::{print, Print},
+    parser::{
+        error::ParseError,
+        line::{
+            binary_op::{binary_op, BinaryOp},
+            bracketed_identifier::{bracketed_identifier, BracketedIdentifier},
+            come_from::{come_from, ComeFrom},
+            comparison::{comparison, Comparison},
+            equality::{equality, Equality},
+            group::{group, Group},
+            identifier::{identifier, Identifier},
+            literal::{literal, Literal},
+            logical::{logical, not, Logical, Not},
+            print::{print, Print},
+            r#else::{r#else, Else},
+            read_input::{read_input, ReadInput},
+            record_type::{record_type, RecordType},
+            LineResult,
+        },
+        span::{Input, Span},
     },
     parser_chain,
 };
@@ -21,26 +30,165 @@ pub enum Expression {
     ComeFrom(ComeFrom),
     Print(Print),
     BinaryOp(BinaryOp),
+    Comparison(Comparison),
+    Logical(Logical),
+    Not(Not),
+    Group(Group),
     Literal(Literal),
     Identifier(Identifier),
+    /// An identifier in indirection brackets, e.g. `(x)`, which refers to the variable named by
+    /// the value of `x`, or with a field access, e.g. `p.x`. A plain identifier is always an
+    /// `Identifier` instead.
+    BracketedIdentifier(BracketedIdentifier),
+    RecordType(RecordType),
+    Else(Else),
+    ReadInput(ReadInput),
     None,
+    /// A line that only continues a multi-line string from the line before. A statement that is
+    /// written over several lines belongs to the last of them, and each of the lines before it is a
+    /// `Continuation`, which does nothing. It is never produced by `expression` itself.
+    Continuation,
+    /// Placeholder for a line that failed to parse. It is never produced by `expression` itself,
+    /// only by `program_recovering`, so that the rest of the program can still be inspected.
+    Error(ParseError),
+}
+
+impl Expression {
+    /// The span of the expression, or `None` for an empty expression or a line that failed to
+    /// parse.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Expression::Equality(eq) => Some(eq.span()),
+            Expression::ComeFrom(c) => Some(c.span()),
+            Expression::Print(p) => Some(p.span()),
+            Expression::BinaryOp(op) => Some(op.span()),
+            Expression::Comparison(c) => Some(c.span()),
+            Expression::Logical(l) => Some(l.span()),
+            Expression::Not(n) => Some(n.span()),
+            Expression::Group(g) => Some(g.span()),
+            Expression::Literal(lit) => Some(lit.span()),
+            Expression::Identifier(ident) => Some(ident.span()),
+            Expression::BracketedIdentifier(ident) => Some(ident.span()),
+            Expression::RecordType(r) => Some(r.span()),
+            Expression::Else(e) => Some(e.span()),
+            Expression::ReadInput(r) => Some(r.span()),
+            Expression::None | Expression::Continuation | Expression::Error(_) => None,
+        }
+    }
+
+    /// The first `and`, `or` or `not` in the expression, including nested ones.
+    pub(crate) fn first_connective(&self) -> Option<&Expression> {
+        match self {
+            Expression::Logical(_) | Expression::Not(_) => Some(self),
+            Expression::Equality(eq) => eq.rhs.expr.first_connective(),
+            Expression::Print(p) => p.expr.first_connective(),
+            Expression::BinaryOp(op) => {
+                let (first, ops) = op.chain();
+                std::iter::once(first)
+                    .chain(ops.into_iter().map(|op| op.rhs.as_ref()))
+                    .find_map(Expression::first_connective)
+            }
+            Expression::Comparison(c) => {
+                let (first, comparisons) = c.chain();
+                std::iter::once(first)
+                    .chain(comparisons.into_iter().map(|c| c.rhs.as_ref()))
+                    .find_map(Expression::first_connective)
+            }
+            Expression::Group(g) => g.expr.first_connective(),
+            Expression::ComeFrom(_)
+            | Expression::Literal(_)
+            | Expression::Identifier(_)
+            | Expression::BracketedIdentifier(_)
+            | Expression::RecordType(_)
+            | Expression::Else(_)
+            | Expression::ReadInput(_)
+            | Expression::None
+            | Expression::Continuation
+            | Expression::Error(_) => None,
+        }
+    }
+
+    /// Calls `f` on every `come from` in the expression, including nested ones.
+    pub(crate) fn for_each_come_from(&mut self, f: &mut impl FnMut(&mut ComeFrom)) {
+        match self {
+            Expression::Equality(eq) => eq.rhs.expr.for_each_come_from(f),
+            Expression::ComeFrom(c) => f(c),
+            Expression::Print(p) => p.expr.for_each_come_from(f),
+            Expression::BinaryOp(op) => {
+                for operand in op.operands_mut() {
+                    operand.for_each_come_from(f);
+                }
+            }
+            Expression::Comparison(c) => {
+                for operand in c.operands_mut() {
+                    operand.for_each_come_from(f);
+                }
+            }
+            Expression::Logical(l) => {
+                for operand in l.operands_mut() {
+                    operand.for_each_come_from(f);
+                }
+            }
+            Expression::Not(n) => n.expr.for_each_come_from(f),
+            Expression::Group(g) => g.expr.for_each_come_from(f),
+            Expression::Literal(_)
+            | Expression::Identifier(_)
+            | Expression::BracketedIdentifier(_)
+            | Expression::RecordType(_)
+            | Expression::Else(_)
+            | Expression::ReadInput(_)
+            | Expression::None
+            | Expression::Continuation
+            | Expression::Error(_) => {}
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Equality(eq) => eq.fmt(f),
+            Expression::ComeFrom(c) => c.fmt(f),
+            Expression::Print(p) => p.fmt(f),
+            Expression::BinaryOp(op) => op.fmt(f),
+            Expression::Comparison(c) => c.fmt(f),
+            Expression::Logical(l) => l.fmt(f),
+            Expression::Not(n) => n.fmt(f),
+            Expression::Group(g) => g.fmt(f),
+            Expression::Literal(lit) => lit.fmt(f),
+            Expression::Identifier(ident) => ident.fmt(f),
+            Expression::BracketedIdentifier(ident) => ident.fmt(f),
+            Expression::RecordType(r) => r.fmt(f),
+            Expression::Else(e) => e.fmt(f),
+            Expression::ReadInput(r) => r.fmt(f),
+            Expression::None | Expression::Continuation => Ok(()),
+            // Reproduce the line as it was written, without its indentation
+            Expression::Error(e) => f.write_str(e.line.trim_start_matches(' ')),
+        }
+    }
 }
 
-pub fn expression(input: &str, use_all_input: bool) -> IResult<&str, Expression> {
-    if input == "" && use_all_input {
-        return Ok(("", Expression::None));
+pub fn expression(input: Input, use_all_input: bool) -> LineResult<Expression> {
+    if input.is_empty() && use_all_input {
+        return Ok((input, Expression::None));
     }
 
     parser_chain!(
-        |i| equality(i).map(|(input, expr)| (input, Expression::Equality(expr))),
-        |i| come_from(i).map(|(input, expr)| (input, Expression::ComeFrom(expr))),
-        |i| print(i).map(|(input, expr)| (input, Expression::Print(expr))),
-        |i| binary_op(i).map(|(input, expr)| (input, Expression::BinaryOp(expr))),
-        |i| literal(i, use_all_input).map(|(input, expr)| (input, Expression::Literal(expr))),
-        |i| identifier(i).map(|(input, expr)| (input, Expression::Identifier(expr)));
+        "logical" => |i| logical(i).map(|(input, expr)| (input, Expression::Logical(expr))),
+        "not" => |i| not(i).map(|(input, expr)| (input, Expression::Not(expr))),
+        "equality" => |i| equality(i).map(|(input, expr)| (input, Expression::Equality(expr))),
+        "come_from" => |i| come_from(i).map(|(input, expr)| (input, Expression::ComeFrom(expr))),
+        "print" => |i| print(i).map(|(input, expr)| (input, Expression::Print(expr))),
+        "record_type" => |i| record_type(i).map(|(input, expr)| (input, Expression::RecordType(expr))),
+        "comparison" => |i| comparison(i).map(|(input, expr)| (input, Expression::Comparison(expr))),
+        "binary_op" => |i| binary_op(i).map(|(input, expr)| (input, Expression::BinaryOp(expr))),
+        "group" => |i| group(i).map(|(input, expr)| (input, Expression::Group(expr))),
+        "literal" => |i| literal(i, use_all_input).map(|(input, expr)| (input, Expression::Literal(expr))),
+        "else" => |i| r#else(i).map(|(input, expr)| (input, Expression::Else(expr))),
+        "input" => |i| read_input(i).map(|(input, expr)| (input, Expression::ReadInput(expr))),
+        "identifier" => |i| identifier(i).map(|(input, expr)| (input, Expression::Identifier(expr))),
+        "bracketed_identifier" => |i| bracketed_identifier(i).map(|(input, expr)| (input, Expression::BracketedIdentifier(expr)));
         input,
         use_all_input
-    );
-
-    Err(Err::Failure(Error::new(input, ErrorKind::Fail)))
+    )
 }
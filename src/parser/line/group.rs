@@ -0,0 +1,86 @@
+use std::fmt::Display;
+
+use nom::{
+    error::{ErrorKind, ParseError, VerboseError},
+    Slice,
+};
+
+use crate::parser::{
+    line::{
+        expression::{expression, Expression},
+        lexer::matching_paren,
+        LineResult,
+    },
+    span::{Input, Span},
+};
+
+/// An expression in parentheses, which is evaluated on its own before being used in the
+/// expression around it, e.g. the `(b * c)` in `a + (b * c)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub(crate) expr: Box<Expression>,
+    pub(crate) span: Span,
+}
+
+impl Group {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The expression inside the parentheses.
+    pub fn expr(&self) -> &Expression {
+        &self.expr
+    }
+}
+
+impl Display for Group {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({})", self.expr)
+    }
+}
+
+/// A parenthesised expression. Parentheses around just an identifier, or around an identifier that
+/// is already in brackets, aren't a group, since they are the indirection brackets of a
+/// `BracketedIdentifier`, and neither are empty parentheses.
+pub fn group(input: Input) -> LineResult<Group> {
+    let Some(close) = matching_paren(&input) else {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Char,
+        )));
+    };
+
+    let inner = input.slice(1..close);
+    let (_, expr) = expression(inner, true)?;
+    let is_indirection = match &expr {
+        Expression::Identifier(_) => true,
+        Expression::BracketedIdentifier(ident) => ident.fields.is_empty(),
+        _ => false,
+    };
+    if is_indirection || matches!(expr, Expression::None) {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            inner,
+            ErrorKind::Verify,
+        )));
+    }
+
+    let rest = input.slice(close + 1..);
+    Ok((
+        rest,
+        Group {
+            expr: Box::new(expr),
+            span: Span::new(input, rest),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::testing::run;
+
+    #[test]
+    fn nested_brackets_are_indirection() {
+        let source = "a = 5\nb = the string a\nptr = the string b\nx = ((ptr))\nprint x\n";
+        assert_eq!(run(source), "5\n");
+    }
+}
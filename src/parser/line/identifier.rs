@@ -1,10 +1,52 @@
-use nom::{bytes::complete::take_while1, IResult};
+use std::fmt::Display;
+
+use nom::bytes::complete::take_while1;
+
+use crate::parser::{
+    line::LineResult,
+    span::{Input, Span},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Identifier(pub(crate) String);
+pub struct Identifier {
+    pub(crate) name: String,
+    pub(crate) span: Span,
+}
+
+impl Identifier {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl From<String> for Identifier {
+    fn from(name: String) -> Self {
+        Self {
+            name,
+            span: Span::default(),
+        }
+    }
+}
 
-pub fn identifier(input: &str) -> IResult<&str, Identifier> {
+pub fn identifier(input: Input) -> LineResult<Identifier> {
+    let start = input;
     let (input, name) = take_while1(|c: char| c.is_ascii_alphabetic() || c == ' ')(input)?;
 
-    Ok((input, Identifier(name.to_owned())))
+    Ok((
+        input,
+        Identifier {
+            name: name.fragment().to_string(),
+            span: Span::new(start, input),
+        },
+    ))
 }
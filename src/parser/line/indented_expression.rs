@@ -1,22 +1,120 @@
-use nom::{character::complete::char, multi::many0_count, IResult};
+use std::fmt::Display;
 
-use crate::parser::line::expression::{expression, Expression};
+use nom::{
+    character::complete::char,
+    combinator::opt,
+    multi::many0_count,
+    sequence::{pair, terminated},
+};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::parser::{
+    line::{
+        comment::{split_comment, Comment},
+        expression::{expression, Expression},
+        label::{label, Label},
+        LineResult,
+    },
+    span::{Input, Span},
+};
+
+#[derive(Debug, Clone, Eq)]
 pub struct IndentedExpression {
     pub(crate) expr: Expression,
     pub(crate) indent_depth: u32,
+    /// The label defined at the start of the line, if any.
+    pub(crate) label: Option<Label>,
+    /// The comment at the end of the line, if any.
+    pub(crate) comment: Option<Comment>,
+    pub(crate) span: Span,
+}
+
+impl IndentedExpression {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The statement on the line.
+    pub fn expr(&self) -> &Expression {
+        &self.expr
+    }
+
+    /// The number of spaces that the line is indented by.
+    pub fn indent_depth(&self) -> u32 {
+        self.indent_depth
+    }
+
+    /// The label defined at the start of the line, if any.
+    pub fn label(&self) -> Option<&Label> {
+        self.label.as_ref()
+    }
+
+    /// The comment at the end of the line, if any.
+    pub fn comment(&self) -> Option<&Comment> {
+        self.comment.as_ref()
+    }
+}
+
+/// Labels (which are only another way of writing line numbers) and comments don't affect the
+/// meaning of the line, so they aren't compared.
+impl PartialEq for IndentedExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.expr == other.expr && self.indent_depth == other.indent_depth
+    }
+}
+
+impl Display for IndentedExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&" ".repeat(self.indent_depth as usize))?;
+        match (&self.label, &self.expr) {
+            (Some(label), Expression::None) if self.comment.is_none() => write!(f, "{label}:")?,
+            (Some(label), expr) => write!(f, "{label}: {expr}")?,
+            (None, expr) => write!(f, "{expr}")?,
+        }
+        match (&self.comment, &self.expr) {
+            (Some(comment), Expression::None) => write!(f, "{comment}"),
+            (Some(comment), _) => write!(f, " {comment}"),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+pub fn indented_expression(input: Input, use_all_input: bool) -> LineResult<IndentedExpression> {
+    indented(input, use_all_input, false)
+}
+
+/// Parses a whole line of a program. Unlike `indented_expression`, this allows the line to start
+/// with a label definition and end with a comment.
+pub fn program_line(input: Input) -> LineResult<IndentedExpression> {
+    indented(input, true, true)
 }
 
-pub fn indented_expression(input: &str, use_all_input: bool) -> IResult<&str, IndentedExpression> {
+fn indented(
+    input: Input,
+    use_all_input: bool,
+    is_program_line: bool,
+) -> LineResult<IndentedExpression> {
+    let start = input;
     let (input, n) = many0_count(char(' '))(input)?;
-    let (_, expr) = expression(input, use_all_input)?;
+    let (input, label, comment, line_end) = if is_program_line {
+        let (input, label) = opt(terminated(label, pair(char(':'), opt(char(' ')))))(input)?;
+        let (line_end, (input, comment)) = split_comment(input)?;
+        (input, label, comment, Some(line_end))
+    } else {
+        (input, None, None, None)
+    };
+    let (input, expr) = expression(input, use_all_input)?;
+
+    // The comment (if any) comes after the expression
+    let end = line_end.unwrap_or(input);
 
     Ok((
-        "",
+        end,
         IndentedExpression {
             expr,
             indent_depth: n as u32,
+            label,
+            comment,
+            span: Span::new(start, end),
         },
     ))
 }
@@ -0,0 +1,87 @@
+use std::fmt::Display;
+
+use nom::{bytes::complete::take_while1, character::complete::char};
+
+use crate::parser::{
+    line::LineResult,
+    span::{Input, Span},
+};
+
+/// A name for a line, which can be used in place of its line number in `come from`. A line is
+/// labelled by starting it (after the indentation) with `@name:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub(crate) name: String,
+    pub(crate) span: Span,
+}
+
+impl Label {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The name of the label, without the `@`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "@{}", self.name)
+    }
+}
+
+pub fn label(input: Input) -> LineResult<Label> {
+    let start = input;
+    let (input, _) = char('@')(input)?;
+    let (input, name) = take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_')(input)?;
+
+    Ok((
+        input,
+        Label {
+            name: name.fragment().to_string(),
+            span: Span::new(start, input),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{line::expression::Expression, program::program};
+
+    #[test]
+    fn labels_stand_for_line_numbers() {
+        let labelled = program("@top: x = 1\nprint x\n@end:\ncome from @top\ncome from @end\n");
+        let numbered = program("x = 1\nprint x\n\ncome from 1\ncome from 3\n");
+        assert_eq!(labelled, numbered);
+    }
+
+    #[test]
+    fn undefined_labels_are_errors() {
+        let errors = program("x = 1\ncome from @nowhere\n").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line_number(), 2);
+        assert_eq!(errors[0].column(), 11);
+        assert_eq!(errors[0].parser(), Some("come_from"));
+        assert_eq!(errors[0].text(), "@nowhere");
+    }
+
+    #[test]
+    fn duplicate_labels_are_errors() {
+        let errors = program("@a: x = 1\n@a: x = 2\ncome from @a\n").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line_number(), 2);
+        assert_eq!(errors[0].column(), 1);
+        assert_eq!(errors[0].parser(), Some("label"));
+    }
+
+    #[test]
+    fn labels_on_multi_line_strings_are_on_the_last_line() {
+        let parsed = program("@s: print the string a\\\nb\\\nc\ncome from @s\n").unwrap();
+        let Expression::ComeFrom(c) = parsed.lines()[3].expr() else {
+            panic!("expected a come from");
+        };
+        assert_eq!(c.line_number().value(), 3);
+    }
+}
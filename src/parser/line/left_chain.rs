@@ -0,0 +1,108 @@
+/// Implements a node that is one link of a chain of operators nested on the left, e.g. `a + b * c`
+/// is a `*` whose LHS is `a + b`. Chains on generated lines can be thousands of operators long, so
+/// everything that goes along a chain does it in a loop rather than recursing, which would
+/// overflow the stack.
+///
+/// The node needs `lhs`, `rhs` and `span` fields, and a field for its operator, which has a
+/// `symbol`. It gets `chain` and `operands_mut` methods, and implementations of `Clone`,
+/// `PartialEq`, `Eq`, `Drop` and `Display`.
+#[macro_export]
+macro_rules! left_chain {
+    ($node:ident, $variant:path, $operator:ident) => {
+        impl $node {
+            /// The first operand of the chain that ends with this link, and the links of the chain
+            /// from first to last.
+            pub(crate) fn chain(
+                &self,
+            ) -> (&$crate::parser::line::expression::Expression, Vec<&$node>) {
+                let mut links = vec![self];
+                let mut first = self.lhs.as_ref();
+                while let $variant(link) = first {
+                    links.push(link);
+                    first = link.lhs.as_ref();
+                }
+                links.reverse();
+                (first, links)
+            }
+
+            /// All of the operands of the chain that ends with this link, from first to last.
+            pub(crate) fn operands_mut(
+                &mut self,
+            ) -> Vec<&mut $crate::parser::line::expression::Expression> {
+                let mut operands = vec![self.rhs.as_mut()];
+                let mut lhs = self.lhs.as_mut();
+                loop {
+                    match lhs {
+                        $variant(link) => {
+                            operands.push(link.rhs.as_mut());
+                            lhs = link.lhs.as_mut();
+                        }
+                        first => {
+                            operands.push(first);
+                            break;
+                        }
+                    }
+                }
+                operands.reverse();
+                operands
+            }
+        }
+
+        impl Clone for $node {
+            fn clone(&self) -> Self {
+                let (first, links) = self.chain();
+                let mut lhs = first.clone();
+                for link in links {
+                    lhs = $variant($node {
+                        lhs: Box::new(lhs),
+                        rhs: link.rhs.clone(),
+                        $operator: link.$operator,
+                        span: link.span,
+                    });
+                }
+
+                let $variant(node) = lhs else { unreachable!() };
+                node
+            }
+        }
+
+        impl PartialEq for $node {
+            fn eq(&self, other: &Self) -> bool {
+                let (first, links) = self.chain();
+                let (other_first, other_links) = other.chain();
+                first == other_first
+                    && links.len() == other_links.len()
+                    && links
+                        .iter()
+                        .zip(other_links)
+                        .all(|(a, b)| a.$operator == b.$operator && a.rhs == b.rhs)
+            }
+        }
+
+        impl Eq for $node {}
+
+        impl Drop for $node {
+            fn drop(&mut self) {
+                // Take each LHS out of the chain before it's dropped, so that each link is dropped
+                // with an empty LHS
+                use $crate::parser::line::expression::Expression;
+
+                let mut lhs = std::mem::replace(self.lhs.as_mut(), Expression::None);
+                while let $variant(link) = &mut lhs {
+                    lhs = std::mem::replace(link.lhs.as_mut(), Expression::None);
+                }
+            }
+        }
+
+        impl std::fmt::Display for $node {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let (first, links) = self.chain();
+                write!(f, "{first}")?;
+                for link in links {
+                    write!(f, " {} {}", link.$operator.symbol(), link.rhs)?;
+                }
+                Ok(())
+            }
+        }
+    };
+}
@@ -0,0 +1,179 @@
+// Splitting an expression into operators and the operands between them.
+
+use nom::Slice;
+use strum::IntoEnumIterator;
+
+use crate::parser::{
+    line::{binary_op::Operation, comparison::Comparator, logical::Connective},
+    span::{Input, Span},
+};
+
+/// A token of an expression. An operator has to be surrounded by spaces, which are part of the
+/// operator token, and everything between two operators is a single operand, which is parsed
+/// separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    Operand(Input<'a>),
+    Operator(Operator, Span),
+}
+
+/// Anything that can go between two operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Operation(Operation),
+    Comparator(Comparator),
+    Connective(Connective),
+}
+
+impl Operator {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Operation(op) => op.symbol(),
+            Self::Comparator(c) => c.symbol(),
+            Self::Connective(c) => c.symbol(),
+        }
+    }
+
+    fn iter() -> impl Iterator<Item = Self> {
+        Operation::iter()
+            .map(Self::Operation)
+            .chain(Comparator::iter().map(Self::Comparator))
+            .chain(Connective::iter().map(Self::Connective))
+    }
+}
+
+/// Splits the input into tokens in a single pass from left to right. The tokens alternate between
+/// operands and operators, starting and ending with an operand (which may be empty). Operators
+/// inside parentheses belong to the parenthesised group, so they don't split the operand.
+/// Comparators and connectives (e.g. `<` and `and`) after the start of a string literal are part
+/// of the string, so they don't split the operand either, and nor do parentheses in the string.
+pub fn tokens(input: Input) -> Vec<Token> {
+    let mut tokens = Vec::new();
+
+    // The start of the current operand, and how far into it has been searched for an operator
+    let mut rest = input;
+    let mut searched = 0;
+    // How deeply nested in parentheses the search is, and whether a string literal has started
+    // outside of them
+    let mut depth = 0usize;
+    let mut in_string = false;
+    while let Some(c) = rest[searched..].chars().next() {
+        match c {
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth = depth.saturating_sub(1),
+            't' if depth == 0 && rest[searched..].starts_with("the string ") => in_string = true,
+            ' ' if depth == 0 => {
+                if let Some((op, len)) = operator(&rest[searched..])
+                    && (matches!(op, Operator::Operation(_)) || !in_string)
+                {
+                    let operator = rest.slice(searched..);
+                    let after = operator.slice(len..);
+                    tokens.push(Token::Operand(rest.slice(..searched)));
+                    tokens.push(Token::Operator(op, Span::new(operator, after)));
+
+                    rest = after;
+                    searched = 0;
+                    in_string = false;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        searched += c.len_utf8();
+    }
+    tokens.push(Token::Operand(rest));
+
+    tokens
+}
+
+/// Splits the input at the operators that `splits` picks out of its tokens. Each part comes with the
+/// operator before it, which is `None` for the first part.
+pub fn split_at<'a>(
+    input: Input<'a>,
+    tokens: Vec<Token<'a>>,
+    splits: impl Fn(Operator) -> bool,
+) -> Vec<(Option<Operator>, Input<'a>)> {
+    // Slicing counts the lines that it skips over, so only slice what's left each time, to keep
+    // this linear
+    let mut parts = Vec::new();
+    let mut rest = input;
+    let mut before = None;
+    for token in tokens {
+        if let Token::Operator(op, span) = token
+            && splits(op)
+        {
+            let offset = rest.location_offset();
+            parts.push((before, rest.slice(..span.start - offset)));
+            rest = rest.slice(span.end - offset..);
+            before = Some(op);
+        }
+    }
+    parts.push((before, rest));
+
+    parts
+}
+
+/// The index of the `)` that closes the `(` at the start of the input, if there is one. Every
+/// parenthesis in between counts, including ones in string literals.
+pub fn matching_paren(input: &str) -> Option<usize> {
+    if !input.starts_with('(') {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (idx, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The operator at the start of the input, and its length including the spaces around it.
+fn operator(input: &str) -> Option<(Operator, usize)> {
+    let input = input.strip_prefix(' ')?;
+    Operator::iter().find_map(|op| {
+        let symbol = op.symbol();
+        let after = input.strip_prefix(symbol)?;
+        after.starts_with(' ').then_some((op, symbol.len() + 2))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::testing::run;
+
+    #[test]
+    fn comparators_in_strings_are_text() {
+        let source = "\
+print the string 1 < 2
+s = the string x ≠ y
+print s
+print 1 < 2
+";
+        assert_eq!(run(source), "1 < 2\nx ≠ y\n1\n");
+    }
+
+    #[test]
+    fn connectives_in_parentheses_belong_to_the_group() {
+        let source = "\
+x = 1
+y = 2
+y = 2 and x = (0 or 1)
+ print 1
+x = 0
+y = 0
+x = (y or 1) and y = 5
+ print 2
+";
+        // The last guard is `(x = (y or 1)) and (y = 5)`, which is false
+        assert_eq!(run(source), "1\n");
+    }
+}
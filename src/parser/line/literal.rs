@@ -1,10 +1,21 @@
+use std::fmt::Display;
+
 use nom::{
-    bytes::{complete::take_while1, streaming::tag},
-    error::{Error, ErrorKind},
-    Err, IResult,
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::char,
+    combinator::{opt, value},
+    error::{ErrorKind, ParseError, VerboseError},
+    Slice,
 };
 
-use crate::parser_chain;
+use crate::{
+    parser::{
+        line::LineResult,
+        span::{Input, Span},
+    },
+    parser_chain,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Literal {
@@ -12,31 +23,315 @@ pub enum Literal {
     String(StringLit),
 }
 
-pub fn literal(input: &str, use_all_input: bool) -> IResult<&str, Literal> {
+impl Literal {
+    pub fn span(&self) -> Span {
+        match self {
+            Literal::Integer(lit) => lit.span(),
+            Literal::String(lit) => lit.span(),
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Integer(lit) => lit.fmt(f),
+            Literal::String(lit) => lit.fmt(f),
+        }
+    }
+}
+
+pub fn literal(input: Input, use_all_input: bool) -> LineResult<Literal> {
     parser_chain!(
-        |i| integer(i).map(|(input, lit)| (input, Literal::Integer(lit))),
-        |i| string(i).map(|(input, lit)| (input, Literal::String(lit)));
+        "integer" => |i| integer(i).map(|(input, lit)| (input, Literal::Integer(lit))),
+        "string" => |i| string(i).map(|(input, lit)| (input, Literal::String(lit)));
         input,
         use_all_input
-    );
-
-    Err(Err::Failure(Error::new(input, ErrorKind::Fail)))
+    )
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct IntegerLit(pub(crate) i128);
+pub struct IntegerLit {
+    pub(crate) value: i128,
+    /// The type that the literal was forced to have, if any.
+    pub(crate) suffix: Option<IntegerSuffix>,
+    pub(crate) span: Span,
+}
+
+/// A suffix that forces the type of an integer literal, e.g. `5z` is a `z` rather than an `int`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerSuffix {
+    Int,
+    Z,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct StringLit(pub(crate) String);
+pub struct StringLit {
+    pub(crate) value: String,
+    /// The string as it was written in the source, before escapes were processed.
+    pub(crate) raw: String,
+    pub(crate) span: Span,
+}
+
+impl IntegerLit {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn value(&self) -> i128 {
+        self.value
+    }
+
+    /// The type that the literal was forced to have, if any.
+    pub fn suffix(&self) -> Option<IntegerSuffix> {
+        self.suffix
+    }
+}
+
+impl StringLit {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The string, with its escapes replaced by what they stand for.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The string as it was written in the source, before escapes were processed.
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl Display for IntegerLit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)?;
+        match self.suffix {
+            Some(suffix) => suffix.fmt(f),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for IntegerSuffix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegerSuffix::Int => f.write_str("int"),
+            IntegerSuffix::Z => f.write_str("z"),
+        }
+    }
+}
+
+impl Display for StringLit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the string {}", self.raw)
+    }
+}
 
-pub fn integer(input: &str) -> IResult<&str, IntegerLit> {
+/// An integer literal: an optional `-`, some digits, and optionally `int` or `z` to force its
+/// type. Without a suffix, the literal is an `int` if it fits in one and a `z` otherwise. It is an
+/// error for an `int` literal to be negative or greater than 126.
+pub fn integer(input: Input) -> LineResult<IntegerLit> {
+    let start = input;
+    let (input, negative) = opt(char('-'))(input)?;
+    let (input, IntegerLit { value: n, .. }) = unsigned_integer(input)?;
+    let n = if negative.is_some() { -n } else { n };
+
+    let suffix_start = input;
+    let (input, suffix) = opt(alt((
+        value(IntegerSuffix::Int, tag("int")),
+        value(IntegerSuffix::Z, tag("z")),
+    )))(input)?;
+    if suffix == Some(IntegerSuffix::Int) && !(0..127).contains(&n) {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            suffix_start,
+            ErrorKind::TooLarge,
+        )));
+    }
+
+    Ok((
+        input,
+        IntegerLit {
+            value: n,
+            suffix,
+            span: Span::new(start, input),
+        },
+    ))
+}
+
+/// A non-negative integer without a suffix, e.g. a line number.
+pub fn unsigned_integer(input: Input) -> LineResult<IntegerLit> {
+    let start = input;
     let (input, lit) = take_while1(|c: char| c.is_ascii_digit())(input)?;
+    let value = lit.parse().map_err(|_| {
+        nom::Err::Failure(VerboseError::from_error_kind(input, ErrorKind::TooLarge))
+    })?;
 
-    Ok((input, IntegerLit(lit.parse().unwrap())))
+    Ok((
+        input,
+        IntegerLit {
+            value,
+            suffix: None,
+            span: Span::new(start, input),
+        },
+    ))
 }
 
-pub fn string(input: &str) -> IResult<&str, StringLit> {
+/// A string literal, which is `the string ` followed by the rest of the input. The string can
+/// contain the escapes `\n` (newline), `\t` (tab), `\s` (space, e.g. to make trailing spaces
+/// visible), `\\` (backslash) and `\u{...}` (a unicode code point in hex). A backslash at the end
+/// of a line continues the string onto the next line, with a newline in between.
+pub fn string(input: Input) -> LineResult<StringLit> {
+    let start = input;
     let (input, _) = tag("the string ")(input)?;
+    let value = unescape(input)?;
+    let rest = input.slice(input.len()..);
+
+    Ok((
+        rest,
+        StringLit {
+            value,
+            raw: input.fragment().to_string(),
+            span: Span::new(start, rest),
+        },
+    ))
+}
+
+fn unescape(input: Input) -> Result<String, nom::Err<VerboseError<Input>>> {
+    let error = |idx: usize| {
+        nom::Err::Failure(VerboseError::from_error_kind(
+            input.slice(idx..),
+            ErrorKind::Escaped,
+        ))
+    };
+
+    let mut value = String::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        match chars.next().map(|(_, c)| c) {
+            Some('n') | Some('\n') => value.push('\n'),
+            Some('\r') if chars.next_if(|&(_, c)| c == '\n').is_some() => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('s') => value.push(' '),
+            Some('\\') => value.push('\\'),
+            Some('u') => {
+                if chars.next_if(|&(_, c)| c == '{').is_none() {
+                    return Err(error(idx));
+                }
+                let mut hex = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| c != '}') {
+                    hex.push(c);
+                }
+                let c = chars
+                    .next()
+                    .and_then(|_| u32::from_str_radix(&hex, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| error(idx))?;
+                value.push(c);
+            }
+            _ => return Err(error(idx)),
+        }
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        interpreter::{
+            testing::run,
+            value::{int::Int, z::Z, Value},
+        },
+        parser::{
+            error::ParseError,
+            line::{
+                expression::Expression,
+                literal::{IntegerSuffix, Literal},
+            },
+            program::program,
+        },
+    };
+
+    /// Parses `source` as the RHS of an assignment, which has to be a literal.
+    fn literal(source: &str) -> Result<Literal, Vec<ParseError>> {
+        let parsed = program(&format!("x = {source}\n"))?;
+        let Expression::Equality(eq) = parsed.lines()[0].expr() else {
+            panic!("expected an equality");
+        };
+        let Expression::Literal(lit) = eq.rhs().expr() else {
+            panic!("expected a literal");
+        };
+        Ok(lit.clone())
+    }
+
+    #[test]
+    fn integer_literals() {
+        for (source, suffix, value) in [
+            ("5", None, Value::Int(Int(5))),
+            ("126", None, Value::Int(Int(126))),
+            ("127", None, Value::Z(Z(127))),
+            ("200", None, Value::Z(Z(200))),
+            ("-3", None, Value::Z(Z(-3))),
+            ("7z", Some(IntegerSuffix::Z), Value::Z(Z(7))),
+            ("-7z", Some(IntegerSuffix::Z), Value::Z(Z(-7))),
+            ("7int", Some(IntegerSuffix::Int), Value::Int(Int(7))),
+        ] {
+            let lit = literal(source).unwrap();
+            let Literal::Integer(integer) = &lit else {
+                panic!("expected an integer literal");
+            };
+            assert_eq!(integer.suffix(), suffix, "{source}");
+            assert_eq!(Value::from(&lit), value, "{source}");
+            assert_eq!(lit.to_string(), source);
+        }
+    }
+
+    #[test]
+    fn int_literals_out_of_range_are_errors() {
+        for (source, column) in [("200int", 8), ("-1int", 7), ("127int", 8)] {
+            let errors = literal(source).unwrap_err();
+            assert_eq!(errors.len(), 1, "{source}");
+            assert_eq!(errors[0].line_number(), 1);
+            assert_eq!(errors[0].column(), column, "{source}");
+        }
+    }
+
+    #[test]
+    fn escapes_in_strings() {
+        let source = r"the string a\nb\tc\sd\\e\u{41}\u{1F600}";
+        let lit = literal(source).unwrap();
+        let Literal::String(string) = &lit else {
+            panic!("expected a string literal");
+        };
+        assert_eq!(string.value(), "a\nb\tc d\\eA\u{1F600}");
+        assert_eq!(lit.to_string(), source);
+    }
+
+    #[test]
+    fn bad_escapes_are_errors() {
+        for source in [
+            r"the string a\qb",
+            r"the string a\u41",
+            r"the string a\u{zz}",
+            r"the string a\",
+        ] {
+            let errors = literal(source).unwrap_err();
+            assert_eq!(errors.len(), 1, "{source}");
+            assert_eq!(errors[0].column(), 17, "{source}");
+        }
+    }
 
-    Ok(("", StringLit(input.to_owned())))
+    #[test]
+    fn multi_line_strings_are_on_their_last_line() {
+        // `come from 2` refers to the whole string, so `print 3` is skipped
+        let source = "print the string one\\\ntwo\nprint 3\ncome from 2\nprint 4\n";
+        assert_eq!(run(source), "one\ntwo\n4\n");
+    }
 }
@@ -0,0 +1,214 @@
+use std::fmt::Display;
+
+use nom::{
+    bytes::complete::tag,
+    error::{ErrorKind, ParseError, VerboseError},
+};
+use strum_macros::EnumIter;
+
+use crate::{
+    left_chain,
+    parser::{
+        line::{
+            expression::{expression, Expression},
+            lexer::{split_at, tokens, Operator},
+            LineResult,
+        },
+        span::{Input, Span},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Connective {
+    And,
+    Or,
+}
+
+impl Connective {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::And => "and",
+            Self::Or => "or",
+        }
+    }
+}
+
+/// Two conditions joined by `and` or `or`, e.g. `x = 1 and y < 2`. The conditions are evaluated
+/// as conditionals (so an equality compares rather than assigns), and the RHS is only evaluated if
+/// it is needed. Connectives can only be used on a line that guards a block. It is one link in a
+/// chain of connectives (see `left_chain!`), and the RHS of an `or` can be a chain of `and`s.
+#[derive(Debug)]
+pub struct Logical {
+    pub(crate) lhs: Box<Expression>,
+    pub(crate) rhs: Box<Expression>,
+    pub(crate) connective: Connective,
+    pub(crate) span: Span,
+}
+
+impl Logical {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The LHS, which is the rest of the chain before this connective.
+    pub fn lhs(&self) -> &Expression {
+        &self.lhs
+    }
+
+    pub fn rhs(&self) -> &Expression {
+        &self.rhs
+    }
+
+    pub fn connective(&self) -> Connective {
+        self.connective
+    }
+}
+
+left_chain!(Logical, Expression::Logical, connective);
+
+/// The negation of a condition, e.g. `not x = 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Not {
+    pub(crate) expr: Box<Expression>,
+    pub(crate) span: Span,
+}
+
+impl Not {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The condition that is negated.
+    pub fn expr(&self) -> &Expression {
+        &self.expr
+    }
+}
+
+impl Display for Not {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not {}", self.expr)
+    }
+}
+
+/// Conditions joined by connectives, which bind less tightly than anything else, including the
+/// `=` of an equality. `and` binds more tightly than `or`, and otherwise they are applied from
+/// left to right.
+pub fn logical(input: Input) -> LineResult<Logical> {
+    let parts = split_at(input, tokens(input), |op| {
+        matches!(op, Operator::Connective(_))
+    });
+    if parts.len() == 1 {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Fail,
+        )));
+    }
+
+    // The `or`s join chains of `and`s, each of which starts at `and_start`
+    let join = |lhs, rhs, connective, start, end| {
+        Expression::Logical(Logical {
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            connective,
+            span: Span::new(start, end),
+        })
+    };
+    let mut parts = parts.into_iter();
+    let (_, first) = parts.next().unwrap();
+    let (mut rest, mut ands) = expression(first, true)?;
+    let mut and_start = first;
+    let mut ors = None;
+    for (before, part) in parts {
+        let Some(Operator::Connective(connective)) = before else {
+            unreachable!()
+        };
+        let rhs;
+        ands = match connective {
+            Connective::And => {
+                (rest, rhs) = expression(part, true)?;
+                join(ands, rhs, Connective::And, and_start, rest)
+            }
+            Connective::Or => {
+                ors = Some(match ors {
+                    Some(ors) => join(ors, ands, Connective::Or, input, rest),
+                    None => ands,
+                });
+                (rest, rhs) = expression(part, true)?;
+                and_start = part;
+                rhs
+            }
+        };
+    }
+    let lhs = match ors {
+        Some(ors) => join(ors, ands, Connective::Or, input, rest),
+        None => ands,
+    };
+
+    let Expression::Logical(logical) = lhs else {
+        unreachable!()
+    };
+    Ok((rest, logical))
+}
+
+pub fn not(input: Input) -> LineResult<Not> {
+    let start = input;
+    let (input, _) = tag("not ")(input)?;
+    let (input, expr) = expression(input, true)?;
+
+    Ok((
+        input,
+        Not {
+            expr: Box::new(expr),
+            span: Span::new(start, input),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{interpreter::testing::run, parser::program::program};
+
+    #[test]
+    fn connectives_outside_guards_are_errors() {
+        let sources = [
+            "x = 1 and y = 2\n",
+            "f = 3 or 0\n",
+            "not x = 1\nprint 1\n",
+            "salt and pepper = 5\n",
+        ];
+        for source in sources {
+            let errors = program(source).unwrap_err();
+            assert_eq!(errors.len(), 1, "{source}");
+            assert_eq!(errors[0].line_number(), 1);
+            assert_eq!(errors[0].column(), 1);
+        }
+    }
+
+    #[test]
+    fn nested_connectives_outside_guards_are_errors() {
+        for (source, column) in [("x = (1 and 0)\n", 6), ("print (x = 0 or x = 5)\n", 8)] {
+            let errors = program(source).unwrap_err();
+            assert_eq!(errors.len(), 1, "{source}");
+            assert_eq!(errors[0].column(), column, "{source}");
+            assert_eq!(errors[0].parser(), Some("logical"));
+        }
+
+        let source = "x = 5\ny = 1\ny = (x = 0 or x = 5)\n print 1\n";
+        assert_eq!(run(source), "1\n");
+    }
+
+    #[test]
+    fn connectives_in_guards() {
+        let source = "\
+x = 1
+y = 2
+x = 1 and y = 3
+ print 1
+x = 2 or y = 2
+ print 2
+not x = 2
+ print 3
+";
+        assert_eq!(run(source), "2\n3\n");
+    }
+}
@@ -1,11 +1,33 @@
+/// Tries each named parser in turn and returns the first result that uses all of the input (or
+/// any successful result, if `use_all_input` is false).
+///
+/// If every parser fails, the error that got furthest into the input is returned, tagged with the
+/// name of the parser that produced it. If none of them got past the start of the input, no
+/// parser is to blame more than any other, so the error isn't tagged.
 #[macro_export]
 macro_rules! parser_chain {
-    ($($parser:expr),*; $input:ident, $use_all_input:ident) => {
+    ($($name:literal => $parser:expr),*; $input:ident, $use_all_input:ident) => {{
+        use nom::error::{ContextError, ErrorKind, ParseError, VerboseError};
+
+        let mut furthest: Option<(VerboseError<$crate::parser::span::Input>, &'static str)> = None;
+        let position = |e: &VerboseError<$crate::parser::span::Input>| e.errors[0].0.location_offset();
         $(let result = $parser($input);
-        if let Ok((input, _)) = result {
-            if input.is_empty() || !$use_all_input {
-                return result;
-            }
+        let error = match result {
+            Ok((input, _)) if input.is_empty() || !$use_all_input => return result,
+            // The parser succeeded but didn't use all of the input, so the error is whatever
+            // was left over
+            Ok((input, _)) => VerboseError::from_error_kind(input, ErrorKind::Eof),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e,
+            Err(nom::Err::Incomplete(_)) => VerboseError::from_error_kind($input, ErrorKind::Eof),
+        };
+        if furthest.as_ref().map_or(true, |(f, _)| position(&error) > position(f)) {
+            furthest = Some((error, $name));
         })*
-    }
+
+        let (error, name) = furthest.unwrap();
+        if position(&error) == $input.location_offset() {
+            return Err(nom::Err::Failure(error));
+        }
+        Err(nom::Err::Failure(VerboseError::add_context(error.errors[0].0, name, error)))
+    }};
 }
@@ -1,13 +1,48 @@
-use nom::{bytes::streaming::tag, IResult};
+use std::fmt::Display;
 
-use crate::parser::line::expression::{expression, Expression};
+use nom::bytes::streaming::tag;
+
+use crate::parser::{
+    line::{
+        expression::{expression, Expression},
+        LineResult,
+    },
+    span::{Input, Span},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Print(pub(crate) Box<Expression>);
+pub struct Print {
+    pub(crate) expr: Box<Expression>,
+    pub(crate) span: Span,
+}
+
+impl Print {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The expression that is printed.
+    pub fn expr(&self) -> &Expression {
+        &self.expr
+    }
+}
+
+impl Display for Print {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "print {}", self.expr)
+    }
+}
 
-pub fn print(input: &str) -> IResult<&str, Print> {
+pub fn print(input: Input) -> LineResult<Print> {
+    let start = input;
     let (input, _) = tag("print ")(input)?;
     let (input, expr) = expression(input, true)?;
 
-    Ok((input, Print(Box::new(expr))))
+    Ok((
+        input,
+        Print {
+            expr: Box::new(expr),
+            span: Span::new(start, input),
+        },
+    ))
 }
@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use nom::bytes::complete::tag;
+
+use crate::parser::{
+    line::LineResult,
+    span::{Input, Span},
+};
+
+/// `input`, which reads a line from the interpreter's input as a string, e.g. `x = input`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadInput {
+    pub(crate) span: Span,
+}
+
+impl ReadInput {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Display for ReadInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("input")
+    }
+}
+
+pub fn read_input(input: Input) -> LineResult<ReadInput> {
+    let start = input;
+    let (input, _) = tag("input")(input)?;
+
+    Ok((
+        input,
+        ReadInput {
+            span: Span::new(start, input),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{interpreter::InterpreterState, testing::run_with};
+
+    fn run_reading(input: &str, source: &str) -> String {
+        run_with(
+            InterpreterState::capturing().with_input(input.as_bytes()),
+            source,
+        )
+    }
+
+    #[test]
+    fn input_is_cast_to_the_type_of_the_variable() {
+        let source = "n = int\nn = input\nprint n + 1\nbig = z\nbig = input\nprint big + 1\ns = input\nprint s\n";
+        assert_eq!(
+            run_reading("41\n1000\nhello\n", source),
+            "42\n1001\nhello\n"
+        );
+    }
+
+    #[test]
+    fn line_endings_are_stripped() {
+        let source = "a = input\nb = input\nc = input\nprint a\nprint b\nprint c\n";
+        assert_eq!(
+            run_reading("one\r\ntwo\nthree", source),
+            "one\ntwo\nthree\n"
+        );
+    }
+
+    #[test]
+    fn the_end_of_the_input_is_an_uninitialised_string() {
+        let source = "a = input\nb = input\nprint b\nb\n print 1\nelse\n print 0\nb = 5\nprint b\n";
+        assert_eq!(run_reading("only line\n", source), "nothing\n0\n5\n");
+    }
+}
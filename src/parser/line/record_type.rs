@@ -0,0 +1,91 @@
+use std::fmt::Display;
+
+use nom::{
+    bytes::complete::tag,
+    error::{ErrorKind, ParseError, VerboseError},
+    multi::separated_list1,
+    Slice,
+};
+
+use crate::parser::{
+    line::{
+        identifier::{identifier, Identifier},
+        LineResult,
+    },
+    span::{Input, Span},
+};
+
+/// The definition of a record type, e.g. `record x int, y int`. It only does anything as the RHS
+/// of an equality, which defines a record type whose name is the LHS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordType {
+    /// The name and type name of each field, in order.
+    pub(crate) fields: Vec<(Identifier, Identifier)>,
+    pub(crate) span: Span,
+}
+
+impl RecordType {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The name and type name of each field, in order.
+    pub fn fields(&self) -> &[(Identifier, Identifier)] {
+        &self.fields
+    }
+}
+
+impl Display for RecordType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("record ")?;
+        for (i, (name, type_name)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name} {type_name}")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn record_type(input: Input) -> LineResult<RecordType> {
+    let start = input;
+    let (input, _) = tag("record ")(input)?;
+    let (input, fields) = separated_list1(tag(", "), field)(input)?;
+
+    Ok((
+        input,
+        RecordType {
+            fields,
+            span: Span::new(start, input),
+        },
+    ))
+}
+
+/// A field of a record type, which is its name and then the name of its type, separated by a
+/// space. Names can contain spaces, so the type name is everything after the last space.
+fn field(input: Input) -> LineResult<(Identifier, Identifier)> {
+    let (rest, ident) = identifier(input)?;
+    let Some(space) = ident
+        .name
+        .rfind(' ')
+        .filter(|&i| i > 0 && i < ident.name.len() - 1)
+    else {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Space,
+        )));
+    };
+
+    let type_start = input.slice(space + 1..);
+    let name = Identifier {
+        name: ident.name[..space].to_owned(),
+        span: Span::new(input, input.slice(space..)),
+    };
+    let type_name = Identifier {
+        name: ident.name[space + 1..].to_owned(),
+        span: Span::new(type_start, rest),
+    };
+
+    Ok((rest, (name, type_name)))
+}
@@ -1,15 +1,304 @@
-use crate::parser::line::indented_expression::{indented_expression, IndentedExpression};
+use std::{collections::HashMap, fmt::Display};
+
+use nom::{Offset, Slice};
+
+use crate::parser::{
+    error::ParseError,
+    line::{
+        expression::Expression,
+        indented_expression::{program_line, IndentedExpression},
+    },
+    span::{Columns, Input, Span},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Program {
     pub(crate) expressions: Vec<IndentedExpression>,
 }
 
-pub fn program(input: &str) -> Program {
-    Program {
-        expressions: input
-            .lines()
-            .map(|l| indented_expression(l, true).unwrap().1)
-            .collect::<Vec<_>>(),
+impl Program {
+    /// Every line of the program, in order, so that the line numbered `n` is at index `n - 1`.
+    /// Blank lines, lines that failed to parse and the lines before the last line of a multi-line
+    /// statement are all included.
+    pub fn lines(&self) -> &[IndentedExpression] {
+        &self.expressions
+    }
+
+    /// The errors for all of the lines that failed to parse, in line order.
+    pub fn errors(&self) -> impl Iterator<Item = &ParseError> {
+        self.expressions.iter().filter_map(|e| match &e.expr {
+            Expression::Error(error) => Some(error),
+            _ => None,
+        })
+    }
+}
+
+/// Prints the program as source code. Parsing the output gives back an identical program.
+impl Display for Program {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Continuation lines are printed as part of the multi-line statement that follows them
+        for expr in self
+            .expressions
+            .iter()
+            .filter(|e| !matches!(e.expr, Expression::Continuation))
+        {
+            writeln!(f, "{expr}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a whole program. If any lines fail to parse, an error is returned for each of them.
+pub fn program(input: &str) -> Result<Program, Vec<ParseError>> {
+    let program = program_recovering(input);
+    let errors = program.errors().cloned().collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        Ok(program)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses a whole program, replacing any lines that fail to parse with `Expression::Error`. Every
+/// line of the input produces exactly one expression, so line numbers are unaffected by errors.
+///
+/// A string that ends in a backslash continues onto the next line. The statement containing it
+/// belongs to the last line it's written on, and the lines before that are
+/// `Expression::Continuation`s with the same indentation, so that they don't change the block
+/// structure of the program.
+pub fn program_recovering(input: &str) -> Program {
+    let columns = Columns::new(input);
+    let input = Input::new_extra(input, &columns);
+    let physical_lines = lines(input);
+
+    // The source of each expression: the whole statement for a multi-line statement, and a single
+    // line for everything else
+    let mut lines = Vec::new();
+    let mut expressions = Vec::new();
+
+    let mut first = 0;
+    while first < physical_lines.len() {
+        let mut last = first;
+        let mut continues = continues_string(physical_lines[first].fragment());
+        while continues && last + 1 < physical_lines.len() {
+            last += 1;
+            continues = ends_with_escape(physical_lines[last].fragment());
+        }
+
+        let line = if first == last {
+            physical_lines[first]
+        } else {
+            let start = physical_lines[first].location_offset();
+            let end = physical_lines[last].location_offset() + physical_lines[last].len();
+            input.slice(start..end)
+        };
+        let expr = match program_line(line) {
+            Ok((_, expr)) => expr,
+            Err(e) => error_line(line, ParseError::new(line, e)),
+        };
+
+        for &l in &physical_lines[first..last] {
+            lines.push(l);
+            expressions.push(IndentedExpression {
+                expr: Expression::Continuation,
+                indent_depth: expr.indent_depth,
+                label: None,
+                comment: None,
+                span: Span::new(l, l.slice(l.len()..)),
+            });
+        }
+        lines.push(line);
+        expressions.push(expr);
+
+        first = last + 1;
+    }
+
+    resolve_labels(&lines, &mut expressions);
+    check_connectives(&lines, &mut expressions);
+
+    Program { expressions }
+}
+
+/// Whether the line has a string literal (rather than a comment that mentions one) that continues
+/// onto the next line.
+fn continues_string(line: &str) -> bool {
+    match line.find("the string ") {
+        Some(idx) => !line[..idx].contains('#') && ends_with_escape(line),
+        None => false,
+    }
+}
+
+/// Whether the line ends with a backslash that isn't itself escaped.
+fn ends_with_escape(line: &str) -> bool {
+    (line.len() - line.trim_end_matches('\\').len()) % 2 == 1
+}
+
+/// A line that failed to parse. It keeps the indentation of the line, so that the block structure
+/// of the program is unchanged.
+fn error_line(line: Input, error: ParseError) -> IndentedExpression {
+    IndentedExpression {
+        expr: Expression::Error(error),
+        indent_depth: (line.len() - line.trim_start_matches(' ').len()) as u32,
+        label: None,
+        comment: None,
+        span: Span::new(line, line.slice(line.len()..)),
+    }
+}
+
+/// Fills in the line numbers of all `come from`s that use a label. Lines that define a label that
+/// was already defined, or that use a label that isn't defined, are replaced by errors.
+fn resolve_labels(lines: &[Input], expressions: &mut [IndentedExpression]) {
+    let mut errors = Vec::new();
+
+    let mut labels = HashMap::new();
+    for (i, expr) in expressions.iter().enumerate() {
+        let Some(label) = &expr.label else { continue };
+        if let Some(first) = labels.get(&label.name) {
+            let message = format!("label `{label}` is already defined on line {first}");
+            errors.push((i, ParseError::at(lines[i], label.span, "label", message)));
+        } else {
+            labels.insert(label.name.clone(), i + 1);
+        }
+    }
+
+    for (i, expr) in expressions.iter_mut().enumerate() {
+        expr.expr.for_each_come_from(&mut |c| {
+            let Some(label) = &c.label else { return };
+            if let Some(&line_number) = labels.get(&label.name) {
+                c.line_number.value = line_number as i128;
+            } else {
+                let message = format!("label `{label}` is not defined");
+                errors.push((
+                    i,
+                    ParseError::at(lines[i], label.span, "come_from", message),
+                ));
+            }
+        });
+    }
+
+    for (i, error) in errors {
+        // Only report the first error on each line
+        if !matches!(expressions[i].expr, Expression::Error(_)) {
+            expressions[i] = error_line(lines[i], error);
+        }
+    }
+}
+
+/// Replaces lines that use `and`, `or` or `not` anywhere without guarding a block with errors.
+/// Connectives are only ever evaluated as conditionals, so on any other line they would silently do
+/// nothing, e.g. `x = 1 and y = 2` wouldn't assign anything.
+fn check_connectives(lines: &[Input], expressions: &mut [IndentedExpression]) {
+    for i in 0..expressions.len() {
+        let expr = &expressions[i];
+        let Some(connective) = expr.expr.first_connective() else {
+            continue;
+        };
+        // The same rule as the interpreter uses for whether a line is a guard
+        let is_guard = expressions
+            .get(i + 1)
+            .is_some_and(|next| next.indent_depth > expr.indent_depth);
+        if is_guard {
+            continue;
+        }
+
+        let parser = match connective {
+            Expression::Logical(_) => "logical",
+            _ => "not",
+        };
+        let span = connective.span().unwrap();
+        let message = "`and`, `or` and `not` can only be used on a line that guards a block (and \
+                       can't be part of a name)";
+        let error = ParseError::at(lines[i], span, parser, message.to_owned());
+        expressions[i] = error_line(lines[i], error);
+    }
+}
+
+/// Splits the input into lines in the same way as `str::lines`, keeping track of the position of
+/// each line in the input.
+fn lines(input: Input) -> Vec<Input> {
+    let mut rest = input;
+    input
+        .lines()
+        .map(|l| {
+            let start = rest.fragment().offset(&l);
+            let line = rest.slice(start..start + l.len());
+            rest = rest.slice(start + l.len()..);
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{
+        line::expression::Expression,
+        program::{program, program_recovering},
+    };
+
+    /// The contents of each fenced code block in the README.
+    fn readme_examples() -> Vec<String> {
+        include_str!("../../README.md")
+            .split("```")
+            .skip(1)
+            .step_by(2)
+            .map(|block| block.trim_start_matches('\n').to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn readme_examples_round_trip() {
+        let examples = readme_examples();
+        assert!(!examples.is_empty());
+
+        for source in examples {
+            let parsed = program(&source).unwrap();
+            let printed = parsed.to_string();
+            let reparsed = program(&printed).unwrap();
+            assert_eq!(parsed, reparsed, "round trip changed\n{source}");
+            assert_eq!(printed, reparsed.to_string());
+        }
+    }
+
+    #[test]
+    fn spans_locate_nodes_in_the_source() {
+        let source = "x = 1\na = n ÷÷ d\n";
+        let parsed = program(source).unwrap();
+        let Expression::Equality(eq) = parsed.lines()[1].expr() else {
+            panic!("expected an equality");
+        };
+        assert_eq!(eq.lhs().identifier().name(), "a");
+
+        let rhs = eq.rhs().span();
+        assert_eq!((rhs.line(), rhs.column()), (2, 5));
+        assert_eq!(&source[rhs.start()..rhs.end()], "n ÷÷ d");
+
+        let Expression::BinaryOp(op) = eq.rhs().expr() else {
+            panic!("expected a binary operation");
+        };
+        let d = op.rhs().span().unwrap();
+        assert_eq!((d.line(), d.column()), (2, 10));
+        assert_eq!((d.start(), d.end()), (17, 18));
+    }
+
+    #[test]
+    fn every_bad_line_is_reported_at_once() {
+        let source = "x = 1\n%%%\nprint x\nprint %%\nx = 200int\ny = x + 1\n";
+        let parsed = program_recovering(source);
+
+        let errors = parsed.errors().collect::<Vec<_>>();
+        let line_numbers = errors.iter().map(|e| e.line_number()).collect::<Vec<_>>();
+        assert_eq!(line_numbers, [2, 4, 5]);
+        assert_eq!(program(source).unwrap_err().len(), 3);
+
+        // The other lines are still there, with their own line numbers
+        assert_eq!(parsed.lines().len(), 6);
+        assert!(matches!(parsed.lines()[1].expr(), Expression::Error(_)));
+        assert!(matches!(parsed.lines()[2].expr(), Expression::Print(_)));
+        let Expression::Equality(eq) = parsed.lines()[5].expr() else {
+            panic!("expected an equality");
+        };
+        assert_eq!(eq.lhs().identifier().name(), "y");
+        assert_eq!(eq.span().line(), 6);
     }
 }
@@ -0,0 +1,81 @@
+use std::hash::Hash;
+
+use nom_locate::LocatedSpan;
+
+/// The input type of the line parsers, which keeps track of where it is in the program source.
+pub type Input<'a> = LocatedSpan<&'a str, &'a Columns>;
+
+/// The column of every byte offset in the program source. Looking columns up here, rather than
+/// counting the characters before each node on its line, keeps parsing long lines linear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columns(Vec<usize>);
+
+impl Columns {
+    pub fn new(source: &str) -> Self {
+        let mut columns = Vec::with_capacity(source.len() + 1);
+        let mut column = 1;
+        for c in source.chars() {
+            columns.extend(std::iter::repeat_n(column, c.len_utf8()));
+            column = if c == '\n' { 1 } else { column + 1 };
+        }
+        columns.push(column);
+
+        Self(columns)
+    }
+}
+
+/// The location of a node in the program source.
+///
+/// Spans are ignored when comparing or hashing nodes, so that two nodes with the same meaning
+/// are equal wherever they appear in the source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Span {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) line: u32,
+    pub(crate) column: usize,
+}
+
+impl Span {
+    /// The span from the start of `start` up to (but not including) the start of `end`.
+    pub(crate) fn new(start: Input, end: Input) -> Self {
+        Self {
+            start: start.location_offset(),
+            end: end.location_offset(),
+            line: start.location_line(),
+            column: start.extra.0[start.location_offset()],
+        }
+    }
+
+    /// Byte offset of the start of the span.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte offset of the end of the span (exclusive).
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The 1-based line number that the span starts on.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// The 1-based column (in characters) that the span starts at.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl PartialEq for Span {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Span {}
+
+impl Hash for Span {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
@@ -0,0 +1,325 @@
+// Inserting, deleting and moving lines of a program.
+//
+// `come from` refers to lines by their line number, so these edits also rewrite the target of
+// every `come from` in the program so that it still refers to the same line as before.
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::parser::{
+    line::{expression::Expression, indented_expression::IndentedExpression},
+    program::Program,
+    span::Span,
+};
+
+/// A `come from` that refers to a line that would be deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The line containing the `come from`.
+    pub line_number: usize,
+    /// The deleted line that it refers to.
+    pub target: usize,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {} comes from line {}, which would be deleted",
+            self.line_number, self.target
+        )
+    }
+}
+
+/// Why an edit couldn't be made. The program is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenumberError {
+    /// A line number that isn't in the program (or, where the end of the program is allowed, one
+    /// past its last line).
+    OutOfRange(usize),
+    /// An edit at this line would split a multi-line statement.
+    SplitsStatement(usize),
+    /// Lines can't be moved to inside themselves.
+    MoveIntoItself {
+        lines: RangeInclusive<usize>,
+        to: usize,
+    },
+    /// `come from`s that refer to lines that would be deleted.
+    Conflicts(Vec<Conflict>),
+}
+
+impl Display for RenumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfRange(line) => write!(f, "line {line} is out of range"),
+            Self::SplitsStatement(line) => {
+                write!(f, "line {line} is inside a multi-line statement")
+            }
+            Self::MoveIntoItself { lines, to } => write!(
+                f,
+                "can't move lines {} to {} to inside themselves (line {to})",
+                lines.start(),
+                lines.end()
+            ),
+            Self::Conflicts(conflicts) => {
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{conflict}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A line of the rearranged program: either an existing line (by its old line number), or a new
+/// one.
+enum Line {
+    Old(usize),
+    New(Box<IndentedExpression>),
+}
+
+/// Inserts empty lines before line `at` (or at the end of the program, if `at` is one more than
+/// the number of lines). The new lines have the same indentation as line `at`, so that they don't
+/// change the block structure of the program.
+///
+/// Fails if `at` is out of range or inside a multi-line statement.
+pub fn insert_empty_lines(
+    program: &mut Program,
+    at: usize,
+    count: usize,
+) -> Result<(), RenumberError> {
+    let indent_depth = at
+        .checked_sub(1)
+        .and_then(|i| program.expressions.get(i))
+        .map_or(0, |e| e.indent_depth);
+    let lines = (0..count)
+        .map(|_| IndentedExpression {
+            expr: Expression::None,
+            indent_depth,
+            label: None,
+            comment: None,
+            span: Span::default(),
+        })
+        .collect();
+    insert_lines(program, at, lines)
+}
+
+/// Inserts `lines` before line `at` (or at the end of the program, if `at` is one more than the
+/// number of lines). Any `come from`s in the new lines are left alone, so they should refer to the
+/// line numbers after the insertion.
+///
+/// Fails if `at` is out of range or inside a multi-line statement.
+pub fn insert_lines(
+    program: &mut Program,
+    at: usize,
+    lines: Vec<IndentedExpression>,
+) -> Result<(), RenumberError> {
+    let len = program.expressions.len();
+    check_in_range(at, 1..=len + 1)?;
+    check_not_splitting(program, at)?;
+
+    let new_lines = (1..at)
+        .map(Line::Old)
+        .chain(lines.into_iter().map(|l| Line::New(Box::new(l))))
+        .chain((at..=len).map(Line::Old))
+        .collect();
+
+    // Nothing is deleted, so there can't be any conflicts
+    rearrange(program, new_lines)
+}
+
+/// Deletes the given lines. If any `come from`s in the remaining lines refer to a deleted line,
+/// the program is left unchanged and the conflicts are returned.
+///
+/// Also fails if the lines are out of range, or if they contain only part of a multi-line
+/// statement.
+pub fn delete_lines(
+    program: &mut Program,
+    lines: RangeInclusive<usize>,
+) -> Result<(), RenumberError> {
+    let len = program.expressions.len();
+    check_in_range(*lines.start(), 1..=len)?;
+    check_in_range(*lines.end(), *lines.start()..=len)?;
+    check_not_splitting(program, *lines.start())?;
+    check_not_splitting(program, *lines.end() + 1)?;
+
+    let new_lines = (1..=len)
+        .filter(|l| !lines.contains(l))
+        .map(Line::Old)
+        .collect();
+    rearrange(program, new_lines)
+}
+
+/// Moves the given lines to before line `to` (or to the end of the program, if `to` is one more
+/// than the number of lines). Line numbers refer to the program before the move.
+///
+/// Fails if the lines are out of range, if `to` is inside the lines being moved, or if either the
+/// lines or `to` would split a multi-line statement.
+pub fn move_lines(
+    program: &mut Program,
+    lines: RangeInclusive<usize>,
+    to: usize,
+) -> Result<(), RenumberError> {
+    let len = program.expressions.len();
+    check_in_range(*lines.start(), 1..=len)?;
+    check_in_range(*lines.end(), *lines.start()..=len)?;
+    check_in_range(to, 1..=len + 1)?;
+    check_not_splitting(program, *lines.start())?;
+    check_not_splitting(program, *lines.end() + 1)?;
+    check_not_splitting(program, to)?;
+    if to > *lines.start() && to <= *lines.end() {
+        return Err(RenumberError::MoveIntoItself { lines, to });
+    }
+
+    let new_lines = (1..to)
+        .filter(|l| !lines.contains(l))
+        .chain(lines.clone())
+        .chain((to..=len).filter(|l| !lines.contains(l)))
+        .map(Line::Old)
+        .collect();
+
+    // Nothing is deleted, so there can't be any conflicts
+    rearrange(program, new_lines)
+}
+
+fn check_in_range(line: usize, range: RangeInclusive<usize>) -> Result<(), RenumberError> {
+    if range.contains(&line) {
+        Ok(())
+    } else {
+        Err(RenumberError::OutOfRange(line))
+    }
+}
+
+/// Fails if the boundary just before line `line` is inside a multi-line statement, since the
+/// lines of a multi-line statement have to stay together.
+fn check_not_splitting(program: &Program, line: usize) -> Result<(), RenumberError> {
+    let splits = line >= 2
+        && matches!(
+            program.expressions.get(line - 2).map(|e| &e.expr),
+            Some(Expression::Continuation)
+        );
+    if splits {
+        Err(RenumberError::SplitsStatement(line))
+    } else {
+        Ok(())
+    }
+}
+
+/// Replaces the lines of the program by `new_lines`, rewriting the targets of `come from`s in the
+/// old lines to their new line numbers.
+fn rearrange(program: &mut Program, new_lines: Vec<Line>) -> Result<(), RenumberError> {
+    // Old line number -> new line number, for all lines that are kept
+    let mut line_numbers = vec![None; program.expressions.len() + 1];
+    for (i, line) in new_lines.iter().enumerate() {
+        if let Line::Old(l) = line {
+            line_numbers[*l] = Some(i + 1);
+        }
+    }
+
+    // Targets past the end of the program (or line 0) don't refer to any line, so they are left
+    // alone
+    let new_target = |target: usize| match line_numbers.get(target) {
+        Some(&new) if target != 0 => new,
+        _ => Some(target),
+    };
+
+    let mut conflicts = Vec::new();
+    for line in &new_lines {
+        if let Line::Old(l) = line {
+            program.expressions[l - 1]
+                .expr
+                .for_each_come_from(&mut |c| {
+                    let target = c.line_number.value as usize;
+                    if new_target(target).is_none() {
+                        conflicts.push(Conflict {
+                            line_number: *l,
+                            target,
+                        });
+                    }
+                });
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(RenumberError::Conflicts(conflicts));
+    }
+
+    let mut old_lines = std::mem::take(&mut program.expressions)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    program.expressions = new_lines
+        .into_iter()
+        .map(|line| match line {
+            Line::Old(l) => {
+                let mut expr = old_lines[l - 1].take().unwrap();
+                expr.expr.for_each_come_from(&mut |c| {
+                    let target = c.line_number.value as usize;
+                    c.line_number.value = new_target(target).unwrap() as i128;
+                });
+                expr
+            }
+            Line::New(expr) => *expr,
+        })
+        .collect();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::program::program,
+        renumber::{delete_lines, insert_empty_lines, move_lines, Conflict, RenumberError},
+    };
+
+    const SOURCE: &str = "x = 0\ncome from 3\nx = x + 1\nprint x\n";
+
+    #[test]
+    fn insert_rewrites_come_from() {
+        let mut p = program(SOURCE).unwrap();
+        insert_empty_lines(&mut p, 3, 2).unwrap();
+        assert_eq!(
+            p.to_string(),
+            "x = 0\ncome from 5\n\n\nx = x + 1\nprint x\n"
+        );
+    }
+
+    #[test]
+    fn out_of_range_lines_are_errors() {
+        let mut p = program(SOURCE).unwrap();
+        assert_eq!(
+            insert_empty_lines(&mut p, 99, 1),
+            Err(RenumberError::OutOfRange(99))
+        );
+        assert_eq!(
+            insert_empty_lines(&mut p, 0, 1),
+            Err(RenumberError::OutOfRange(0))
+        );
+        assert_eq!(
+            delete_lines(&mut p, 3..=7),
+            Err(RenumberError::OutOfRange(7))
+        );
+        assert_eq!(
+            move_lines(&mut p, 1..=2, 2),
+            Err(RenumberError::MoveIntoItself {
+                lines: 1..=2,
+                to: 2
+            })
+        );
+        assert_eq!(p, program(SOURCE).unwrap());
+    }
+
+    #[test]
+    fn deleting_a_come_from_target_conflicts() {
+        let mut p = program(SOURCE).unwrap();
+        assert_eq!(
+            delete_lines(&mut p, 3..=3),
+            Err(RenumberError::Conflicts(vec![Conflict {
+                line_number: 2,
+                target: 3
+            }]))
+        );
+    }
+}